@@ -0,0 +1,80 @@
+use crate::scene::Mipmaps;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// How many directories above the executable's own are searched, enough to get out of 'target/<profile>'
+const EXECUTABLE_PARENTS: usize = 2;
+
+/// Resolved path of an image and whether it holds data rather than colors
+type ImageKey = (PathBuf, bool);
+
+/// Decoded images, so each file is only decoded once
+static IMAGES: LazyLock<Mutex<HashMap<ImageKey, Arc<Mipmaps>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug)]
+pub enum AssetError {
+    NotFound(String), // the path does not exist in any of the searched directories
+    Decode(PathBuf, image::ImageError), // the file exists but could not be read as an image
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::NotFound(path) => write!(f, "Asset '{path}' not found"),
+            AssetError::Decode(path, error) => {
+                write!(f, "Failed to load '{}': {error}", path.display())
+            }
+        }
+    }
+}
+
+/// Returns the file 'path' refers to. Relative paths are looked up in the current directory first, then next
+/// to the executable and in the few directories above it, so builds in 'target/release' find the repository's assets.
+pub fn resolve(path: &str) -> Result<PathBuf, AssetError> {
+    let relative = PathBuf::from(path);
+    if relative.is_absolute() {
+        return if relative.exists() {
+            Ok(relative)
+        } else {
+            Err(AssetError::NotFound(path.to_string()))
+        };
+    }
+
+    let current_dir = std::env::current_dir().ok();
+    let executable_dirs = std::env::current_exe()
+        .ok()
+        .map(|exe| {
+            exe.ancestors()
+                .skip(1)
+                .take(1 + EXECUTABLE_PARENTS)
+                .map(PathBuf::from)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    current_dir
+        .into_iter()
+        .chain(executable_dirs)
+        .map(|dir| dir.join(&relative))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| AssetError::NotFound(path.to_string()))
+}
+
+/// Returns the image at 'path' with its mipmaps, decoding the file the first time it is requested.
+pub fn mipmaps(path: &str, data: bool) -> Result<Arc<Mipmaps>, AssetError> {
+    let path = resolve(path)?;
+    let mut images = IMAGES.lock().expect("Image cache is not poisoned");
+    if let Some(mipmaps) = images.get(&(path.clone(), data)) {
+        return Ok(Arc::clone(mipmaps));
+    }
+
+    let image = image::open(&path)
+        .map_err(|error| AssetError::Decode(path.clone(), error))?
+        .to_rgba8();
+    let mipmaps = Arc::new(Mipmaps::make(image, data));
+    images.insert((path, data), Arc::clone(&mipmaps));
+    Ok(mipmaps)
+}
@@ -1,25 +1,101 @@
-use crate::scene::{Collision, ColorType, Material, Scene};
+use crate::scene::{
+    ColorType, HitRecord, Hittable, Light, Material, Pbr, Scene, linear_to_srgb, srgb_to_linear,
+};
 use crate::vectors::Vector;
 
+use image::{GenericImage, Rgb, RgbImage};
+use rand::Rng;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 
+const GLOSSY_SAMPLES: u32 = 8;
+const PIXEL_SAMPLES: u32 = 16; // for cameras with depth of field or motion blur
+const AREA_LIGHT_SAMPLES: u32 = 4; // along each edge of an area light, for soft shadows
+// Wavelengths in nanometers used for the red, green and blue channels of dispersive materials
+const CHANNEL_WAVELENGTHS: [f64; 3] = [650., 550., 450.];
+
 const UP: Vector = Vector {
     x: 0.,
     y: 1.,
     z: 0.,
 };
 
+#[derive(Clone, Copy)]
 pub struct Camera {
     // Global camera position and orientation
     pub coords: Vector,
     pub direction: Vector,
+    // Thin lens, an aperture of 0.0 is a pinhole camera with everything in focus
+    pub aperture: f64,
+    pub focus_distance: f64,
+    pub aperture_blades: Option<u32>, // polygonal aperture for shaped bokeh, round if None
+    // Time interval the shutter is open for, moving objects blur if it isn't empty
+    pub shutter: (f64, f64),
+    pub projection: Projection,
+    pub stereo: Option<Stereo>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Stereo {
+    pub mode: StereoMode,
+    pub interocular: f64, // distance between the eyes
+    pub convergence: f64, // distance at which the lines of sight of both eyes meet
+}
+
+#[derive(Clone, Copy)]
+pub enum StereoMode {
+    Anaglyph,   // red/cyan composite of both eyes
+    SideBySide, // left eye in the left half of the image, squeezed horizontally
+    TopBottom,  // left eye in the top half of the image, squeezed vertically
+}
+
+impl StereoMode {
+    /// Returns the stereo mode with the given name.
+    pub fn from_name(name: &str) -> Option<StereoMode> {
+        match name {
+            "anaglyph" => Some(StereoMode::Anaglyph),
+            "side-by-side" => Some(StereoMode::SideBySide),
+            "top-bottom" => Some(StereoMode::TopBottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Projection {
+    Perspective,
+    Orthographic(f64),            // height of the view in world units
+    Fisheye(FisheyeMapping, f64), // field of view in degrees
+    Equirectangular,              // full 360 degree panorama, rendered with a 2:1 aspect ratio
+}
+
+#[derive(Clone, Copy)]
+pub enum FisheyeMapping {
+    Equidistant, // distance from the center is proportional to the angle
+    Equisolid,   // preserves the area of solid angles
+}
+
+impl Projection {
+    /// Returns the projection with the given name and default parameters.
+    pub fn from_name(name: &str) -> Option<Projection> {
+        match name {
+            "perspective" => Some(Projection::Perspective),
+            "orthographic" => Some(Projection::Orthographic(4.)),
+            "fisheye" => Some(Projection::Fisheye(FisheyeMapping::Equidistant, 180.)),
+            "equisolid" => Some(Projection::Fisheye(FisheyeMapping::Equisolid, 180.)),
+            "equirectangular" => Some(Projection::Equirectangular),
+            _ => None,
+        }
+    }
 }
 
+/// What the rays of one view share: the orientation of the camera and, for stereo, which eye they start from.
 struct CameraBasis {
     // Relative orientation of the camera
     forward: Vector,
     right: Vector,
+    // Which eye of a stereo pair is rendered: -1.0 for the left, 1.0 for the right and 0.0 for a single image
+    eye: f64,
 }
 
 impl Camera {
@@ -27,6 +103,12 @@ impl Camera {
         Camera {
             coords,
             direction: direction.normalized(),
+            aperture: 0.,
+            focus_distance: 3.,
+            aperture_blades: None,
+            shutter: (0., 0.),
+            projection: Projection::Perspective,
+            stereo: None,
         }
     }
     /// Relocates the camera based on the key pressed.
@@ -56,7 +138,7 @@ impl Camera {
 
         Camera {
             coords: self.coords + movement.normalized(),
-            direction: self.direction,
+            ..self
         }
     }
 
@@ -85,8 +167,95 @@ impl Camera {
         .normalized();
 
         Camera {
-            coords: self.coords,
             direction: new_direction,
+            ..*self
+        }
+    }
+
+    /// Changes the aperture radius by 'step', which never drops below 0.0 (a pinhole camera).
+    pub fn adjust_aperture(&self, step: f64) -> Self {
+        Camera {
+            aperture: (self.aperture + step).max(0.),
+            ..*self
+        }
+    }
+
+    /// Switches between a round and a hexagonal aperture.
+    pub fn toggle_aperture_shape(&self) -> Self {
+        Camera {
+            aperture_blades: match self.aperture_blades {
+                Some(_) => None,
+                None => Some(6),
+            },
+            ..*self
+        }
+    }
+
+    /// Opens the shutter for one unit of time or closes it again.
+    pub fn toggle_shutter(&self) -> Self {
+        Camera {
+            shutter: if self.shutter.0 < self.shutter.1 {
+                (0., 0.)
+            } else {
+                (0., 1.)
+            },
+            ..*self
+        }
+    }
+
+    /// Switches to the next projection, cycling through all of them.
+    pub fn next_projection(&self) -> Self {
+        let projection = match self.projection {
+            Projection::Perspective => Projection::Orthographic(4.),
+            Projection::Orthographic(_) => Projection::Fisheye(FisheyeMapping::Equidistant, 180.),
+            Projection::Fisheye(FisheyeMapping::Equidistant, field_of_view) => {
+                Projection::Fisheye(FisheyeMapping::Equisolid, field_of_view)
+            }
+            Projection::Fisheye(FisheyeMapping::Equisolid, _) => Projection::Equirectangular,
+            Projection::Equirectangular => Projection::Perspective,
+        };
+        Camera {
+            projection,
+            ..*self
+        }
+    }
+
+    /// Switches to the next stereo mode, cycling through all of them and a single image.
+    pub fn next_stereo_mode(&self) -> Self {
+        let mode = match self.stereo.map(|stereo| stereo.mode) {
+            None => Some(StereoMode::Anaglyph),
+            Some(StereoMode::Anaglyph) => Some(StereoMode::SideBySide),
+            Some(StereoMode::SideBySide) => Some(StereoMode::TopBottom),
+            Some(StereoMode::TopBottom) => None,
+        };
+        Camera {
+            stereo: mode.map(|mode| Stereo {
+                mode,
+                interocular: 0.1,
+                convergence: 3.,
+            }),
+            ..*self
+        }
+    }
+
+    /// Returns the number of rays averaged for every pixel.
+    fn samples_per_pixel(&self) -> u32 {
+        if self.aperture > 0. || self.shutter.0 < self.shutter.1 {
+            PIXEL_SAMPLES
+        } else {
+            1
+        }
+    }
+
+    /// Focuses the camera on the object seen through the center of the screen.
+    pub fn autofocus(&self, scene: &Scene) -> Self {
+        let center_ray = Ray::new(self.coords, self.direction);
+        match center_ray.trace(scene) {
+            Some(hit) => Camera {
+                focus_distance: (hit.point - self.coords).dot(&self.direction),
+                ..*self
+            },
+            None => *self,
         }
     }
 
@@ -94,10 +263,15 @@ impl Camera {
     fn camera_basis(&self) -> CameraBasis {
         let forward = self.direction;
         let right = forward.cross(&UP).normalized();
-        CameraBasis { forward, right }
+        CameraBasis {
+            forward,
+            right,
+            eye: 0.,
+        }
     }
 
-    /// Generates a ray from the camera's position through a pixel at (x, y) on the screen.
+    /// Generates a ray from the camera's position through a pixel at (x, y) on the screen,
+    /// or None if the pixel lies outside of the image circle of a fisheye projection.
     fn generate_ray(
         &self,
         x: u16,
@@ -106,115 +280,342 @@ impl Camera {
         height: u16,
         aspect_ratio: f64,
         basis: &CameraBasis,
-    ) -> Ray {
+    ) -> Option<Ray> {
         let u = (x as f64 + 0.5) / width as f64 - 0.5;
         let v = (y as f64 + 0.5) / height as f64 - 0.5;
+        let (open, close) = self.shutter;
+        let time = open + (close - open) * rand::thread_rng().r#gen::<f64>();
+        let up = basis.right.cross(&basis.forward);
+
+        let ray = match self.projection {
+            Projection::Perspective => self.perspective_ray(u, v, aspect_ratio, basis),
+            Projection::Orthographic(view_height) => {
+                let origin = self.coords
+                    + basis.right * (u * view_height * aspect_ratio)
+                    + up * (-v * view_height);
+                Ray::new(origin, basis.forward)
+            }
+            Projection::Fisheye(mapping, field_of_view) => {
+                // The image circle touches the top and bottom of the screen
+                let (px, py) = (u * 2.0 * aspect_ratio, -v * 2.0);
+                let radius = (px * px + py * py).sqrt();
+                if radius > 1. {
+                    return None;
+                }
+                let max_angle = field_of_view.to_radians() / 2.;
+                let angle = match mapping {
+                    FisheyeMapping::Equidistant => radius * max_angle,
+                    FisheyeMapping::Equisolid => 2. * (radius * (max_angle / 2.).sin()).asin(),
+                };
+                let sideways = if radius > 0. {
+                    (basis.right * px + up * py) / radius
+                } else {
+                    basis.right
+                };
+                Ray::new(
+                    self.coords,
+                    basis.forward * angle.cos() + sideways * angle.sin(),
+                )
+            }
+            Projection::Equirectangular => {
+                // The full width covers 360 degrees of longitude and the height 180 degrees of latitude
+                let level_forward = Vector::make(basis.forward.x, 0., basis.forward.z).normalized();
+                let longitude = u * std::f64::consts::TAU;
+                let latitude = -v * std::f64::consts::PI;
+                let direction = (level_forward * longitude.cos() + basis.right * longitude.sin())
+                    * latitude.cos()
+                    + UP * latitude.sin();
+                Ray::new(self.coords, direction)
+            }
+        };
+
+        let ray = match self.stereo {
+            Some(stereo) if basis.eye != 0. => {
+                // Panoramas shift every ray sideways to its own direction, so the eyes circle around the camera
+                let sideways = match self.projection {
+                    Projection::Equirectangular if ray.direction.cross(&UP).length() > 1e-9 => {
+                        ray.direction.cross(&UP).normalized()
+                    }
+                    _ => basis.right,
+                };
+                let origin = ray.origin + sideways * (basis.eye * stereo.interocular / 2.);
+                let target = ray.origin + ray.direction * stereo.convergence;
+                Ray::new(origin, target - origin)
+            }
+            _ => ray,
+        };
+
+        // Neighbouring pixels are about this far apart, which decides how blurry textures are sampled
+        let cone = match self.projection {
+            Projection::Perspective => (0., 2.0 / height as f64),
+            Projection::Orthographic(view_height) => (view_height / height as f64, 0.),
+            Projection::Fisheye(_, field_of_view) => {
+                (0., field_of_view.to_radians() / height as f64)
+            }
+            Projection::Equirectangular => (0., std::f64::consts::PI / height as f64),
+        };
+
+        Some(Ray { time, cone, ..ray })
+    }
 
+    /// Generates a ray of the perspective projection through the point (u, v) of the screen.
+    fn perspective_ray(&self, u: f64, v: f64, aspect_ratio: f64, basis: &CameraBasis) -> Ray {
         let direction = basis.forward + basis.right * (u * 2.0 * aspect_ratio) + UP * (-v * 2.0);
+        if self.aperture <= 0. {
+            return Ray::new(self.coords, direction);
+        }
 
-        Ray::new(self.coords, direction)
+        // Rays from all over the lens meet again on the plane in focus
+        let focus_point =
+            self.coords + direction * (self.focus_distance / direction.dot(&basis.forward));
+        let (lens_x, lens_y) = self.sample_aperture();
+        let origin =
+            self.coords + basis.right * (lens_x * self.aperture) + UP * (lens_y * self.aperture);
+
+        Ray::new(origin, focus_point - origin)
     }
 
-    pub fn draw(
+    /// Returns a random point on the aperture, scaled to the unit disk or a regular polygon inscribed in it.
+    fn sample_aperture(&self) -> (f64, f64) {
+        let mut rng = rand::thread_rng();
+        match self.aperture_blades {
+            Some(blades) if blades >= 3 => {
+                // Pick one of the triangles between the center and two neighbouring corners
+                let sector = std::f64::consts::TAU / blades as f64;
+                let corner = rng.gen_range(0..blades) as f64 * sector;
+                let (mut a, mut b) = (rng.r#gen::<f64>(), rng.r#gen::<f64>());
+                if a + b > 1. {
+                    (a, b) = (1. - a, 1. - b);
+                }
+                (
+                    a * corner.cos() + b * (corner + sector).cos(),
+                    a * corner.sin() + b * (corner + sector).sin(),
+                )
+            }
+            _ => {
+                let radius = rng.r#gen::<f64>().sqrt();
+                let angle = std::f64::consts::TAU * rng.r#gen::<f64>();
+                (radius * angle.cos(), radius * angle.sin())
+            }
+        }
+    }
+
+    /// Renders the scene into an image 'width' pixels wide with the given aspect 'ratio'.
+    /// Stereo cameras render both eyes and combine them into the one image.
+    pub fn render(&self, scene: &Scene, width: u16, ratio: (u16, u16), depth: u32) -> RgbImage {
+        let (x_ratio, y_ratio) = ratio;
+        let height = (width as f64 * (y_ratio as f64 / x_ratio as f64)) as u16;
+        let aspect_ratio = x_ratio as f64 / y_ratio as f64;
+
+        let Some(stereo) = self.stereo else {
+            return self.render_view(scene, width, height, aspect_ratio, depth, 0.);
+        };
+        match stereo.mode {
+            StereoMode::Anaglyph => {
+                let left = self.render_view(scene, width, height, aspect_ratio, depth, -1.);
+                let mut image = self.render_view(scene, width, height, aspect_ratio, depth, 1.);
+                // The red channel comes from the left eye and the cyan ones from the right
+                for (pixel, left_pixel) in image.pixels_mut().zip(left.pixels()) {
+                    pixel[0] = left_pixel[0];
+                }
+                image
+            }
+            StereoMode::SideBySide => {
+                let mut image = RgbImage::new(width as u32, height as u32);
+                let left = self.render_view(scene, width / 2, height, aspect_ratio, depth, -1.);
+                let right = self.render_view(scene, width / 2, height, aspect_ratio, depth, 1.);
+                image
+                    .copy_from(&left, 0, 0)
+                    .expect("Left eye fits the image");
+                image
+                    .copy_from(&right, (width / 2) as u32, 0)
+                    .expect("Right eye fits the image");
+                image
+            }
+            StereoMode::TopBottom => {
+                let mut image = RgbImage::new(width as u32, height as u32);
+                let top = self.render_view(scene, width, height / 2, aspect_ratio, depth, -1.);
+                let bottom = self.render_view(scene, width, height / 2, aspect_ratio, depth, 1.);
+                image
+                    .copy_from(&top, 0, 0)
+                    .expect("Left eye fits the image");
+                image
+                    .copy_from(&bottom, 0, (height / 2) as u32)
+                    .expect("Right eye fits the image");
+                image
+            }
+        }
+    }
+
+    /// Renders the view of one 'eye' (see CameraBasis) into an image of the given size.
+    fn render_view(
         &self,
-        canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
         scene: &Scene,
         width: u16,
-        ratio: (u16, u16),
+        height: u16,
+        aspect_ratio: f64,
         depth: u32,
-    ) {
-        let cam_basis = self.camera_basis();
-        let (x_ratio, y_ratio) = ratio;
-        let height = (width as f64 * (y_ratio as f64 / x_ratio as f64)) as u16;
-        let aspect_ratio = x_ratio as f64 / y_ratio as f64;
+        eye: f64,
+    ) -> RgbImage {
+        let cam_basis = CameraBasis {
+            eye,
+            ..self.camera_basis()
+        };
+        let mut image = RgbImage::new(width as u32, height as u32);
 
-        for (_y, row) in (0..height).enumerate() {
+        for row in 0..height {
             println!("Rendering row {}/{}", row + 1, height);
 
             for x in 0..width {
-                let ray = self.generate_ray(x, row as u16, width, height, aspect_ratio, &cam_basis);
-                let color = trace_color(scene, &ray, row as u16, height, depth)
-                    .unwrap_or_else(|| background_color(ray.direction));
-
-                canvas.set_draw_color(color);
-                let _ = canvas.draw_point(sdl2::rect::Point::new(x as i32, row as i32));
+                let samples = self.samples_per_pixel();
+                let mut color = Vector::make(0., 0., 0.);
+                for _ in 0..samples {
+                    if let Some(ray) =
+                        self.generate_ray(x, row, width, height, aspect_ratio, &cam_basis)
+                    {
+                        color = color
+                            + trace_color(scene, &ray, depth)
+                                .unwrap_or_else(|| background_color(ray.direction));
+                    }
+                }
+                // Samples are averaged in linear space and only the final pixel is encoded as sRGB
+                let color = color / samples as f64;
+                let pixel = [
+                    linear_to_srgb(color.x),
+                    linear_to_srgb(color.y),
+                    linear_to_srgb(color.z),
+                ];
+                image.put_pixel(x as u32, row as u32, Rgb(pixel));
             }
         }
+        image
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+        scene: &Scene,
+        width: u16,
+        ratio: (u16, u16),
+        depth: u32,
+    ) {
+        let image = self.render(scene, width, ratio, depth);
+
+        for (x, y, pixel) in image.enumerate_pixels() {
+            canvas.set_draw_color(Color::RGB(pixel[0], pixel[1], pixel[2]));
+            let _ = canvas.draw_point(sdl2::rect::Point::new(x as i32, y as i32));
+        }
     }
 }
 
-fn trace_color(scene: &Scene, ray: &Ray, y: u16, height: u16, depth: u32) -> Option<Color> {
+/// Returns the linear color seen along 'ray', or None if it hits nothing.
+fn trace_color(scene: &Scene, ray: &Ray, depth: u32) -> Option<Vector> {
     if depth == 0 {
-        return Some(Color::RGB(0, 0, 0)); // Max depth reached
+        return Some(Vector::make(0., 0., 0.)); // Max depth reached
     }
 
-    ray.trace(scene).map(|collision| {
-        let object = collision.object;
-        let point = collision.point;
+    ray.trace(scene)
+        .map(|hit| handle_hit(&hit, scene, ray, depth))
+}
 
-        handle_hit(
-            point,
-            object.normal(point),
-            object.material(),
-            scene,
-            ray,
-            y,
-            height,
-            depth,
-        )
-    })
+fn handle_hit(hit: &HitRecord, scene: &Scene, ray: &Ray, depth: u32) -> Vector {
+    let opacity = hit.opacity();
+    if opacity >= 1. {
+        return shade_hit(hit, scene, ray, depth);
+    }
+
+    // See-through parts of the surface let the ray carry on to whatever lies behind them
+    let behind_ray = ray.spawn(hit.point + ray.direction * 0.001, ray.direction);
+    let behind =
+        trace_color(scene, &behind_ray, depth).unwrap_or_else(|| background_color(ray.direction));
+    if opacity <= 0. {
+        return behind;
+    }
+    shade_hit(hit, scene, ray, depth) * opacity + behind * (1. - opacity)
 }
 
-fn handle_hit(
-    point_of_colision: Vector,
-    normal: Vector,
-    material: &Material,
-    scene: &Scene,
-    ray: &Ray,
-    y: u16,
-    height: u16,
-    depth: u32,
-) -> Color {
-    let brightness = compute_lighting(scene, point_of_colision, normal);
-    let base_color = match &material.color {
-        ColorType::Solid(c) => *c,
-        ColorType::Function(f) => f(point_of_colision),
-    };
-    let mut reflected_color: Option<Color> = None;
+/// Returns the linear color of the opaque surface at 'hit'.
+fn shade_hit(hit: &HitRecord, scene: &Scene, ray: &Ray, depth: u32) -> Vector {
+    let (point_of_colision, normal, material) = (hit.point, hit.normal, hit.material);
+    let base_color = surface_color(hit);
+    if let Some(pbr) = &material.pbr {
+        return shade_pbr(hit, base_color, pbr, scene, ray, depth);
+    }
+
+    let brightness = compute_lighting(
+        scene,
+        point_of_colision,
+        normal,
+        hit.geometric_normal,
+        ray.time,
+    );
+    let mut reflected_color: Option<Vector> = None;
     let mut transparency: Option<f64> = None;
-    let mut refracted_color: Option<Color> = None;
+    let mut refracted_color: Option<Vector> = None;
     let mut reflectivity: Option<f64> = None;
 
     if let Some((transparency_value, refraction_index)) = material.transparency {
         transparency = Some(transparency_value);
-        let mut refraction_index = refraction_index;
-        if ray.direction.dot(&normal) > 0. {
-            refraction_index = 1. / refraction_index;
-        }
-        let refraction_dir = ray.direction.refract(&normal, refraction_index);
-        let refracted_ray = Ray {
-            origin: point_of_colision + refraction_dir * 0.001,
-            direction: refraction_dir,
-        };
-        refracted_color = trace_color(scene, &refracted_ray, y, height, depth - 1);
 
-        if refracted_color.is_none() {
-            refracted_color = Some(background_color(refraction_dir));
-        }
+        refracted_color = Some(match (material.dispersion, ray.wavelength) {
+            (Some(abbe_number), None) => {
+                // Every channel is refracted at its own wavelength, which splits white light into a spectrum
+                let [r, g, b] = CHANNEL_WAVELENGTHS.map(|wavelength| {
+                    let index =
+                        dispersed_refraction_index(refraction_index, abbe_number, wavelength);
+                    let ray = Ray {
+                        wavelength: Some(wavelength),
+                        ..ray.spawn(ray.origin, ray.direction)
+                    };
+                    refract_color(hit, index, scene, &ray, depth)
+                });
+                Vector::make(r.x, g.y, b.z)
+            }
+            (Some(abbe_number), Some(wavelength)) => {
+                let index = dispersed_refraction_index(refraction_index, abbe_number, wavelength);
+                refract_color(hit, index, scene, ray, depth)
+            }
+            (None, _) => refract_color(hit, refraction_index, scene, ray, depth),
+        });
     }
 
     if let Some(reflectivity_value) = material.reflectivity {
         reflectivity = Some(reflectivity_value);
         let reflected_dir = ray.direction.reflect(&normal).normalized();
-        let reflected_ray = Ray::new(point_of_colision + normal * 0.001, reflected_dir);
-        reflected_color = trace_color(scene, &reflected_ray, y, height, depth - 1);
-        
-        if reflected_color.is_none() {
-            reflected_color = Some(background_color(reflected_dir));
+        // Offsetting along the geometric normal keeps the ray off the actual surface
+        let reflected_ray = ray.spawn(
+            point_of_colision + hit.geometric_normal * 0.001,
+            reflected_dir,
+        );
+
+        match material.roughness {
+            Some(roughness) if roughness > 0. => {
+                reflected_color = Some(glossy_color(
+                    &reflected_ray,
+                    normal,
+                    roughness,
+                    scene,
+                    depth,
+                ));
+            }
+            _ => {
+                reflected_color = trace_color(scene, &reflected_ray, depth - 1);
+
+                if reflected_color.is_none() {
+                    reflected_color = Some(background_color(reflected_dir));
+                }
+            }
         }
     }
 
-    blend_colors(base_color, reflected_color, refracted_color, reflectivity.unwrap_or(0.), transparency.unwrap_or(0.), brightness)
+    blend_colors(
+        base_color,
+        reflected_color,
+        refracted_color,
+        reflectivity.unwrap_or(0.),
+        transparency.unwrap_or(0.),
+        brightness,
+    )
 }
 
 /*
@@ -297,79 +698,332 @@ fn handle_hit(
 }
 */
 
-/// Applies the illumination factor to a color
-fn scale_color(color: Color, brightness: f64) -> Color {
-    Color::RGB(
-        (color.r as f64 * brightness).clamp(0.0, 255.0) as u8,
-        (color.g as f64 * brightness).clamp(0.0, 255.0) as u8,
-        (color.b as f64 * brightness).clamp(0.0, 255.0) as u8,
-    )
+/// Returns the color seen through the transparent material of 'hit' along the refracted ray.
+fn refract_color(
+    hit: &HitRecord,
+    refraction_index: f64,
+    scene: &Scene,
+    ray: &Ray,
+    depth: u32,
+) -> Vector {
+    let (point_of_colision, normal, material) = (hit.point, hit.normal, hit.material);
+    // The ray either enters the object or leaves it, which changes the stack of media it is inside
+    let mut media = ray.media.clone();
+    let outer_index = ray.refraction_index();
+    let id = material as *const Material as usize;
+    if !hit.front_face {
+        // Objects built from several primitives (such as triangles) have a material per primitive,
+        // so if we didn't enter through this one we leave the last medium with the same index
+        let position = media
+            .iter()
+            .rposition(|medium| medium.id == id)
+            .or_else(|| {
+                media
+                    .iter()
+                    .rposition(|medium| medium.refraction_index == refraction_index)
+            });
+        if let Some(position) = position {
+            media.remove(position);
+        }
+    } else {
+        media.push(Medium {
+            id,
+            refraction_index,
+        });
+    }
+    let inner_index = media.last().map_or(1., |medium| medium.refraction_index);
+
+    let refraction_dir = ray.direction.refract(&normal, inner_index / outer_index);
+    let refracted_ray = Ray {
+        media,
+        ..ray.spawn(point_of_colision + refraction_dir * 0.001, refraction_dir)
+    };
+    let mut refracted_color = trace_color(scene, &refracted_ray, depth - 1)
+        .unwrap_or_else(|| background_color(refraction_dir));
+
+    // Light is absorbed on its way through the object, so we attenuate it by the distance to the exit point
+    if let Some((absorption_color, density)) = material.absorption
+        && hit.front_face
+        && let Some(exit) = refracted_ray.trace(scene)
+    {
+        let distance = (exit.point - point_of_colision).length();
+        refracted_color = absorb(refracted_color, absorption_color, density, distance);
+    }
+
+    refracted_color
+}
+
+/// Returns the refraction index at 'wavelength' (in nanometers) of a material with the given
+/// 'refraction_index' (at the yellow helium d-line) and 'abbe_number', using Cauchy's equation.
+fn dispersed_refraction_index(refraction_index: f64, abbe_number: f64, wavelength: f64) -> f64 {
+    // Fraunhofer F, C and d lines in micrometers
+    let (f_line, c_line, d_line): (f64, f64, f64) = (0.4861, 0.6563, 0.5876);
+    let b = (refraction_index - 1.) / abbe_number / (f_line.powi(-2) - c_line.powi(-2));
+    let a = refraction_index - b / d_line.powi(2);
+    let wavelength = wavelength / 1000.;
+    a + b / (wavelength * wavelength)
+}
+
+/// Averages several rays spread over a Phong lobe around the perfect mirror 'reflected_ray'.
+fn glossy_color(
+    reflected_ray: &Ray,
+    normal: Vector,
+    roughness: f64,
+    scene: &Scene,
+    depth: u32,
+) -> Vector {
+    // Deeper bounces get fewer samples so nested glossy surfaces don't explode the ray count
+    let samples = GLOSSY_SAMPLES.min(1 << depth.saturating_sub(2));
+    let exponent = 2. / roughness.clamp(0.001, 1.).powi(2) - 2.;
+    let (tangent, bitangent) = reflected_ray.direction.orthonormal_basis();
+    let mut rng = rand::thread_rng();
+    let mut color = Vector::make(0., 0., 0.);
+
+    for _ in 0..samples {
+        let cos_theta = rng.r#gen::<f64>().powf(1. / (exponent + 1.));
+        let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+        let phi = 2. * std::f64::consts::PI * rng.r#gen::<f64>();
+        let mut direction = tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + reflected_ray.direction * cos_theta;
+        if direction.dot(&normal) <= 0. {
+            // Samples below the surface are mirrored back above it
+            direction = direction.reflect(&normal);
+        }
+
+        let sample_ray = reflected_ray.spawn(reflected_ray.origin, direction);
+        color = color
+            + trace_color(scene, &sample_ray, depth - 1)
+                .unwrap_or_else(|| background_color(sample_ray.direction));
+    }
+
+    color / samples as f64
+}
+
+/// Applies Beer-Lambert absorption to a color after it travelled 'distance' through an absorbing medium
+fn absorb(color: Vector, absorption_color: Color, density: f64, distance: f64) -> Vector {
+    let transmittance = |channel: u8| (-(1. - channel as f64 / 255.) * density * distance).exp();
+    color.multiply(&Vector::make(
+        transmittance(absorption_color.r),
+        transmittance(absorption_color.g),
+        transmittance(absorption_color.b),
+    ))
 }
 
 /// Blends the reflected and base colors of a point
-fn blend_colors(base: Color, reflected: Option<Color>, refracted: Option<Color>, reflectivity: f64, transparency: f64, brightness: f64) -> Color {
-    let base_color = scale_color(base, brightness);
+fn blend_colors(
+    base: Vector,
+    reflected: Option<Vector>,
+    refracted: Option<Vector>,
+    reflectivity: f64,
+    transparency: f64,
+    brightness: Vector,
+) -> Vector {
+    let base_color = base.multiply(&brightness);
     match (reflected, refracted) {
         (Some(reflected_color), Some(refracted_color)) => {
             let transparency = transparency / 2.;
             let reflectivity = reflectivity / 2.;
             let base_weight = 1. - transparency - reflectivity;
-            return Color::RGB(
-                (base_color.r as f64 * base_weight + reflected_color.r as f64 * reflectivity + refracted_color.r as f64 * transparency) as u8,
-                (base_color.g as f64 * base_weight + reflected_color.g as f64 * reflectivity + refracted_color.g as f64 * transparency) as u8,
-                (base_color.b as f64 * base_weight + reflected_color.b as f64 * reflectivity + refracted_color.b as f64 * transparency) as u8,
-            );
+            base_color * base_weight
+                + reflected_color * reflectivity
+                + refracted_color * transparency
         }
         (Some(reflected_color), None) => {
-            let base_weight = 1. - reflectivity;
-            return Color::RGB(
-                (base_color.r as f64 * base_weight + reflected_color.r as f64 * reflectivity) as u8,
-                (base_color.g as f64 * base_weight + reflected_color.g as f64 * reflectivity) as u8,
-                (base_color.b as f64 * base_weight + reflected_color.b as f64 * reflectivity) as u8,
-            );
+            base_color * (1. - reflectivity) + reflected_color * reflectivity
         }
         (None, Some(refracted_color)) => {
-            let base_weight = 1. - transparency;
-            return Color::RGB(
-                (base_color.r as f64 * base_weight + refracted_color.r as f64 * transparency) as u8,
-                (base_color.g as f64 * base_weight + refracted_color.g as f64 * transparency) as u8,
-                (base_color.b as f64 * base_weight + refracted_color.b as f64 * transparency) as u8,
-            );
+            base_color * (1. - transparency) + refracted_color * transparency
         }
-        _ => return base_color,
+        _ => base_color,
     }
 }
 
-/// Returns the illumination of a point based on its normal, other objects and light sources in the scene
-fn compute_lighting(scene: &Scene, hit_point: Vector, normal: Vector) -> f64 {
-    let mut brightness = scene.ambient_light;
+/// Returns the illumination of a point based on its normal, other objects and light sources in the scene.
+/// Each channel is lit separately, as light passing through colored transparent objects gets tinted.
+/// Shadow rays start off the surface along 'geometric_normal', which bumps don't tilt.
+fn compute_lighting(
+    scene: &Scene,
+    hit_point: Vector,
+    normal: Vector,
+    geometric_normal: Vector,
+    time: f64,
+) -> Vector {
+    let ambient = scene.ambient_light;
+    let mut brightness = Vector::make(ambient, ambient, ambient);
 
     for light in &scene.lights {
-        let light_dir = (light.position - hit_point).normalized();
-        let light_distance = (light.position - hit_point).length();
+        let points = light_points(light);
+        let intensity = light.intensity / points.len() as f64;
+        for light_position in points {
+            let light_dir = (light_position - hit_point).normalized();
+            let contribution = normal.dot(&light_dir).max(0.0) * intensity;
+            brightness = brightness
+                + light_transmittance(scene, hit_point, geometric_normal, light_position, time)
+                    * contribution;
+        }
+    }
 
-        // We slightly offset the start of the shadow ray to prevent self-shadowing
-        let shadow_ray = Ray::new(hit_point + normal * 0.001, light_dir);
+    Vector::make(
+        brightness.x.clamp(0.0, 1.0),
+        brightness.y.clamp(0.0, 1.0),
+        brightness.z.clamp(0.0, 1.0),
+    )
+}
 
-        let in_shadow = if let Some(collision) = shadow_ray.trace(scene) {
-            let dist = (collision.point - hit_point).length();
-            dist < light_distance
-        } else {
-            false
+/// Returns the points that 'light' shines from. Area lights are split into a grid of cells with one point in each,
+/// shifted within its cell so that no two points share a row or a column, which spreads the edges of shadows over
+/// many even steps. The points are the same for every shaded point, so lit surfaces don't turn grainy.
+fn light_points(light: &Light) -> Vec<Vector> {
+    let Some((u, v)) = light.area else {
+        return vec![light.position];
+    };
+
+    let cells = AREA_LIGHT_SAMPLES as f64;
+    let mut points = Vec::new();
+    for i in 0..AREA_LIGHT_SAMPLES {
+        for j in 0..AREA_LIGHT_SAMPLES {
+            let a = (i as f64 + (j as f64 + 0.5) / cells) / cells;
+            let b = (j as f64 + (i as f64 + 0.5) / cells) / cells;
+            points.push(light.position + u * a + v * b);
+        }
+    }
+    points
+}
+
+/// Returns the fraction of each color channel of light shining from 'light_position' that reaches 'hit_point'.
+/// Opaque objects block the light completely, transparent ones filter it by their transparency and color,
+/// and see-through parts of a surface let it pass.
+fn light_transmittance(
+    scene: &Scene,
+    hit_point: Vector,
+    geometric_normal: Vector,
+    light_position: Vector,
+    time: f64,
+) -> Vector {
+    let light_dir = (light_position - hit_point).normalized();
+    let mut transmittance = Vector::make(1., 1., 1.);
+
+    // We slightly offset the start of the shadow ray to prevent self-shadowing
+    let mut shadow_ray = Ray {
+        time,
+        ..Ray::new(hit_point + geometric_normal * 0.001, light_dir)
+    };
+
+    let light_distance = (light_position - hit_point).length();
+    while let Some(hit) = shadow_ray.trace_until(
+        scene,
+        light_distance - (shadow_ray.origin - hit_point).length(),
+    ) {
+        let material = hit.material;
+        let filter = match material.transparency {
+            Some((transparency, _)) => surface_color(&hit) * transparency,
+            None => Vector::make(0., 0., 0.),
         };
+        let opacity = hit.opacity();
+        let clear = 1. - opacity;
+        transmittance =
+            transmittance.multiply(&(filter * opacity + Vector::make(clear, clear, clear)));
+        if transmittance.x.max(transmittance.y).max(transmittance.z) < 0.001 {
+            return Vector::make(0., 0., 0.);
+        }
+
+        shadow_ray = Ray {
+            time,
+            ..Ray::new(hit.point + light_dir * 0.001, light_dir)
+        };
+    }
+
+    transmittance
+}
+
+/// Returns the linear color of the material at the point of its surface that was hit
+fn surface_color(hit: &HitRecord) -> Vector {
+    match &hit.material.color {
+        ColorType::Solid(c) => color_to_vector(*c),
+        ColorType::Function(f) => color_to_vector(f(hit.point)),
+        ColorType::Texture(texture) => texture.sample(hit.uv.0, hit.uv.1, hit.footprint),
+        ColorType::Procedural(procedural) => procedural.color(hit.point, hit.uv),
+        ColorType::Terrain(map) => map.color(hit.point, hit.normal),
+    }
+}
+
+/// Shades a point with a physically based material using a GGX microfacet BRDF for every light
+/// and a single (glossy) reflection ray for the light coming from the rest of the scene.
+fn shade_pbr(
+    hit: &HitRecord,
+    base: Vector,
+    pbr: &Pbr,
+    scene: &Scene,
+    ray: &Ray,
+    depth: u32,
+) -> Vector {
+    let point_of_colision = hit.point;
+    let view = -ray.direction;
+    // Shade the side of the surface that faces the viewer
+    let normal = if hit.front_face {
+        hit.normal
+    } else {
+        -hit.normal
+    };
+    let geometric_normal = if hit.front_face {
+        hit.geometric_normal
+    } else {
+        -hit.geometric_normal
+    };
 
-        if !in_shadow {
-            let contribution = normal.dot(&light_dir).max(0.0) * light.intensity;
-            brightness += contribution;
+    let mut radiance = base * (scene.ambient_light * (1. - pbr.metallic));
+    for light in &scene.lights {
+        let points = light_points(light);
+        let intensity = light.intensity / points.len() as f64;
+        for light_position in points {
+            let transmittance = light_transmittance(
+                scene,
+                point_of_colision,
+                geometric_normal,
+                light_position,
+                ray.time,
+            );
+            let light_dir = (light_position - point_of_colision).normalized();
+            let n_dot_l = normal.dot(&light_dir).max(0.);
+            radiance = radiance
+                + pbr
+                    .brdf(base, normal, view, light_dir)
+                    .multiply(&transmittance)
+                    * (n_dot_l * intensity);
         }
     }
 
-    brightness.clamp(0.0, 1.0)
+    // Light reflected from the environment is weighted by the Fresnel term at the viewing angle
+    let fresnel = pbr.environment_fresnel(base, normal.dot(&view).max(0.));
+    let reflected_dir = ray.direction.reflect(&normal).normalized();
+    let reflected_ray = ray.spawn(point_of_colision + normal * 0.001, reflected_dir);
+    let reflected_color = if pbr.roughness > 0. {
+        glossy_color(
+            &reflected_ray,
+            normal,
+            pbr.roughness * pbr.roughness,
+            scene,
+            depth,
+        )
+    } else {
+        trace_color(scene, &reflected_ray, depth - 1)
+            .unwrap_or_else(|| background_color(reflected_dir))
+    };
+    radiance + reflected_color.multiply(&fresnel)
 }
 
+/// Converts an sRGB color to a vector of its linear channels between 0.0 and 1.0
+fn color_to_vector(color: Color) -> Vector {
+    Vector::make(
+        srgb_to_linear(color.r),
+        srgb_to_linear(color.g),
+        srgb_to_linear(color.b),
+    )
+}
 
-/// Returns a color representing the background based on the direction of the given vector.
-fn background_color(dir: Vector) -> Color {
+/// Returns the linear color of the background based on the direction of the given vector.
+fn background_color(dir: Vector) -> Vector {
     let t = ((dir.y + 1.0) * 0.5).clamp(0.0, 1.0);
 
     let top = (0., 0.0, 128.0);
@@ -379,12 +1033,22 @@ fn background_color(dir: Vector) -> Color {
     let g = 155;
     let b = (1.0 - t) * bottom.2 + t * top.2;
 
-    Color::RGB(r as u8, g as u8, b as u8)
+    color_to_vector(Color::RGB(r as u8, g as u8, b as u8))
 }
 
 pub struct Ray {
     pub origin: Vector,
     pub direction: Vector,
+    pub media: Vec<Medium>, // transparent objects the ray is inside, the last one entered on top
+    pub wavelength: Option<f64>, // in nanometers, set once a dispersive material split the ray into channels
+    pub time: f64,               // moment within the camera's shutter interval the ray was sent at
+    pub cone: (f64, f64), // (width at the origin, spread angle) of the pixel's footprint, used to filter textures
+}
+
+#[derive(Clone, Copy)]
+pub struct Medium {
+    pub id: usize, // address of the object's material, which identifies the object
+    pub refraction_index: f64,
 }
 
 impl Ray {
@@ -392,27 +1056,57 @@ impl Ray {
         Ray {
             origin,
             direction: direction.normalized(),
+            media: Vec::new(),
+            wavelength: None,
+            time: 0.,
+            cone: (0., 0.),
         }
     }
 
-    /// Returns Some of the closest collision of the ray 'self' or None if no collision occurs.
-    pub fn trace<'a>(&'a self, scene: &'a Scene) -> Option<Collision<'a>> {
-        scene
-            .objects
-            .iter()
-            .filter_map(|obj| {
-                obj.hit(self).map(|dist| {
-                    let point = self.origin + self.direction * dist;
-                    (
-                        dist,
-                        Collision {
-                            object: &**obj,
-                            point,
-                        },
-                    )
-                })
-            }) // Returns an iter of collissions of 'self' with objects
-            .min_by(|(d1, _), (d2, _)| d1.partial_cmp(d2).unwrap())
-            .map(|(_, col)| col)
+    /// Returns a new ray from 'origin' in 'direction' that travels through the same media as 'self'.
+    pub fn spawn(&self, origin: Vector, direction: Vector) -> Self {
+        let (_, spread) = self.cone;
+        Ray {
+            origin,
+            direction: direction.normalized(),
+            media: self.media.clone(),
+            wavelength: self.wavelength,
+            time: self.time,
+            cone: (self.footprint((origin - self.origin).length()), spread),
+        }
+    }
+
+    /// Returns the width of the ray's footprint at 'distance' from its origin.
+    pub fn footprint(&self, distance: f64) -> f64 {
+        let (width, spread) = self.cone;
+        width + spread * distance
+    }
+
+    /// Returns the refraction index of the medium the ray is currently travelling through.
+    pub fn refraction_index(&self) -> f64 {
+        self.media
+            .last()
+            .map_or(1., |medium| medium.refraction_index)
+    }
+
+    /// Returns Some of the closest hit of the ray 'self' or None if no collision occurs.
+    pub fn trace<'a>(&self, scene: &'a Scene) -> Option<HitRecord<'a>> {
+        self.trace_until(scene, f64::INFINITY)
+    }
+
+    /// Returns Some of the closest hit of the ray 'self' that is nearer than 't_max', or None if there is none.
+    pub fn trace_until<'a>(&self, scene: &'a Scene, t_max: f64) -> Option<HitRecord<'a>> {
+        // Every object only has to beat the closest hit found so far
+        let mut closest = None;
+        let mut t_max = t_max;
+        let objects = scene.objects.iter().map(|object| object.as_ref());
+        let nodes = scene.nodes.iter().map(|node| node as &dyn Hittable);
+        for object in objects.chain(nodes) {
+            if let Some(hit) = object.hit(self, 0.001, t_max) {
+                t_max = hit.t;
+                closest = Some(hit);
+            }
+        }
+        closest
     }
 }
@@ -0,0 +1,96 @@
+use crate::camera::Ray;
+use crate::scene::{HitRecord, Hittable, Interval};
+
+/// How the insides of the two objects of a 'Csg' are combined.
+#[derive(Clone, Copy)]
+pub enum Operation {
+    Union,        // inside either object
+    Intersection, // inside both objects
+    Difference,   // inside the first object but not the second, which is carved out of it
+}
+
+impl Operation {
+    /// Returns whether a point inside or outside of each object is inside the combined object.
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Operation::Union => in_left || in_right,
+            Operation::Intersection => in_left && in_right,
+            Operation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// A solid made by combining the insides of two other solids, like a sphere with a cube carved out of it.
+/// Only objects that report their 'intervals' have an inside, so planes and single triangles add nothing.
+pub struct Csg {
+    pub operation: Operation,
+    pub left: Box<dyn Hittable>,
+    pub right: Box<dyn Hittable>,
+}
+
+impl Csg {
+    pub fn make(operation: Operation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.intervals(ray)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .find(|hit| t_min < hit.t && hit.t < t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        combine(
+            self.operation,
+            self.left.intervals(ray),
+            self.right.intervals(ray),
+        )
+    }
+}
+
+/// Combines two sorted lists of intervals along the same ray with 'operation' into a sorted list.
+pub fn combine<'a>(
+    operation: Operation,
+    left: Vec<Interval<'a>>,
+    right: Vec<Interval<'a>>,
+) -> Vec<Interval<'a>> {
+    // Every boundary of either object, with which object it belongs to and whether the ray enters it there
+    let mut boundaries: Vec<(HitRecord, bool, bool)> = Vec::new();
+    for (intervals, is_left) in [(left, true), (right, false)] {
+        for interval in intervals {
+            boundaries.push((interval.enter, is_left, true));
+            boundaries.push((interval.exit, is_left, false));
+        }
+    }
+    boundaries.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+    let (mut in_left, mut in_right) = (false, false);
+    let mut combined = Vec::new();
+    let mut enter = None;
+    for (hit, is_left, entering) in boundaries {
+        if is_left {
+            in_left = entering;
+        } else {
+            in_right = entering;
+        }
+
+        // The surface of a carved out hole faces into the object that carves it
+        let hit = match operation {
+            Operation::Difference if !is_left => hit.flipped(),
+            _ => hit,
+        };
+        match (enter.take(), operation.contains(in_left, in_right)) {
+            (None, true) => enter = Some(hit),
+            (Some(enter), false) => combined.push(Interval { enter, exit: hit }),
+            (current, _) => enter = current,
+        }
+    }
+    combined
+}
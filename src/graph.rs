@@ -0,0 +1,142 @@
+use crate::camera::Ray;
+use crate::csg::{self, Operation};
+use crate::scene::{
+    HitRecord, Hittable, Interval, Material, hit_to_scene, hit_transformed, transform_ray,
+};
+use crate::vectors::Matrix4;
+
+/// A named part of a scene graph, placed relative to its parent. It can hold an object, other nodes or both,
+/// and everything below it moves, hides and changes color along with it.
+pub struct Node {
+    pub name: String,
+    transform: Matrix4, // from the node's space to its parent's
+    inverse: Matrix4,
+    pub visible: bool,              // hidden nodes hide everything below them too
+    pub material: Option<Material>, // replaces the materials of everything below the node
+    pub object: Option<Box<dyn Hittable>>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// Makes a node that only groups the given children.
+    pub fn group(name: &str, children: Vec<Node>) -> Self {
+        Node {
+            name: name.to_string(),
+            transform: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+            visible: true,
+            material: None,
+            object: None,
+            children,
+        }
+    }
+
+    /// Makes a node that holds 'object', modeled in the node's space.
+    pub fn object(name: &str, object: Box<dyn Hittable>) -> Self {
+        Node {
+            object: Some(object),
+            ..Node::group(name, Vec::new())
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Matrix4) -> Self {
+        self.set_transform(transform);
+        self
+    }
+
+    pub fn with_material(self, material: Material) -> Self {
+        Node {
+            material: Some(material),
+            ..self
+        }
+    }
+
+    pub fn transform(&self) -> Matrix4 {
+        self.transform
+    }
+
+    /// Places the node relative to its parent with 'transform', which must not flatten it.
+    pub fn set_transform(&mut self, transform: Matrix4) {
+        self.inverse = transform
+            .inverse()
+            .expect("Node transforms can be inverted");
+        self.transform = transform;
+    }
+
+    /// Returns this node or the first node below it with the given name.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(name))
+    }
+
+    /// Returns the closest hit of the node's object and children in the node's own space.
+    fn hit_local(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut closest = None;
+        let mut t_max = t_max;
+        let objects = self.object.iter().map(|object| object.as_ref());
+        let children = self.children.iter().map(|child| child as &dyn Hittable);
+        for object in objects.chain(children) {
+            if let Some(hit) = object.hit(ray, t_min, t_max) {
+                t_max = hit.t;
+                closest = Some(hit);
+            }
+        }
+        closest
+    }
+}
+
+impl Hittable for Node {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        if !self.visible {
+            return None;
+        }
+
+        let mut hit = hit_transformed(
+            &self.transform,
+            &self.inverse,
+            ray,
+            t_min,
+            t_max,
+            |local_ray, t_min, t_max| self.hit_local(local_ray, t_min, t_max),
+        )?;
+        if let Some(material) = &self.material {
+            hit.set_material(material);
+        }
+        Some(hit)
+    }
+
+    /// Returns the intervals of the node's object and children together, as if they were combined in a union.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        if !self.visible {
+            return Vec::new();
+        }
+
+        let (local_ray, scale) = transform_ray(&self.inverse, ray);
+        let objects = self.object.iter().map(|object| object.as_ref());
+        let children = self.children.iter().map(|child| child as &dyn Hittable);
+        let local = objects
+            .chain(children)
+            .map(|object| object.intervals(&local_ray))
+            .reduce(|a, b| csg::combine(Operation::Union, a, b))
+            .unwrap_or_default();
+
+        let to_scene = |hit| {
+            let mut hit = hit_to_scene(hit, &self.transform, &self.inverse, scale);
+            if let Some(material) = &self.material {
+                hit.set_material(material);
+            }
+            hit
+        };
+        local
+            .into_iter()
+            .map(|interval| Interval {
+                enter: to_scene(interval.enter),
+                exit: to_scene(interval.exit),
+            })
+            .collect()
+    }
+}
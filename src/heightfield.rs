@@ -0,0 +1,311 @@
+use crate::assets;
+use crate::camera::Ray;
+use crate::scene::{HitRecord, Hittable, Material, hit_triangle};
+use crate::vectors::Vector;
+use image::RgbaImage;
+
+/// Terrain whose heights are read from a grayscale image, with black at the bottom and white at the top.
+/// The image is laid over the rectangle from 'corner' spanning 'size.x' along x and 'size.z' along z,
+/// with its top row at the lowest z, and rises up to 'size.y' above it. A texture covers it once.
+pub struct Heightfield {
+    pub corner: Vector,
+    pub size: Vector,
+    pub material: Material,
+    heights: Vec<f64>, // between 0.0 and 1.0, a row of samples along x after another
+    normals: Vec<Vector>, // of the smooth surface at each sample
+    samples: (usize, usize), // along x and z
+    ranges: Vec<RangeLevel>, // of heights in ever larger blocks of cells, up to a single block
+}
+
+/// The lowest and highest points within each block of cells of one level of the hierarchy.
+struct RangeLevel {
+    columns: usize,
+    rows: usize,
+    ranges: Vec<(f64, f64)>,
+}
+
+impl Heightfield {
+    /// Makes a heightfield from the image at 'path', which is resolved with 'assets::resolve'.
+    /// Larger images are shrunk to at most 'resolution' samples across, which keeps the grid small.
+    /// If the image can't be loaded, reports why and makes a flat heightfield instead.
+    pub fn from_file_or_flat(
+        path: &str,
+        resolution: u32,
+        corner: Vector,
+        size: Vector,
+        material: Material,
+    ) -> Heightfield {
+        match assets::mipmaps(path, true) {
+            Ok(mipmaps) => {
+                Heightfield::from_image(mipmaps.level(resolution), corner, size, material)
+            }
+            Err(error) => {
+                eprintln!("{error}, using a flat heightfield");
+                Heightfield::from_image(&RgbaImage::new(2, 2), corner, size, material)
+            }
+        }
+    }
+
+    /// Makes a heightfield from the average of the color channels of each pixel of 'image'.
+    pub fn from_image(image: &RgbaImage, corner: Vector, size: Vector, material: Material) -> Self {
+        let samples = (
+            image.width().max(2) as usize,
+            image.height().max(2) as usize,
+        );
+        let heights = (0..samples.1)
+            .flat_map(|j| (0..samples.0).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let x = (i as u32).min(image.width().saturating_sub(1));
+                let y = (j as u32).min(image.height().saturating_sub(1));
+                let pixel = image.get_pixel(x, y).0;
+                (pixel[0] as f64 + pixel[1] as f64 + pixel[2] as f64) / (3. * 255.)
+            })
+            .collect();
+
+        let mut heightfield = Heightfield {
+            corner,
+            size,
+            material,
+            heights,
+            normals: Vec::new(),
+            samples,
+            ranges: Vec::new(),
+        };
+        heightfield.normals = (0..samples.1)
+            .flat_map(|j| (0..samples.0).map(move |i| (i, j)))
+            .map(|(i, j)| heightfield.sample_normal(i, j))
+            .collect();
+        heightfield.ranges = heightfield.range_levels();
+        heightfield
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.samples.0 + i]
+    }
+
+    /// Returns the distance between neighbouring samples along x and z.
+    fn spacing(&self) -> (f64, f64) {
+        (
+            self.size.x / (self.samples.0 - 1) as f64,
+            self.size.z / (self.samples.1 - 1) as f64,
+        )
+    }
+
+    /// Returns the point of the surface at the sample in column 'i' and row 'j'.
+    fn vertex(&self, i: usize, j: usize) -> Vector {
+        let (dx, dz) = self.spacing();
+        self.corner
+            + Vector::make(
+                i as f64 * dx,
+                self.height(i, j) * self.size.y,
+                j as f64 * dz,
+            )
+    }
+
+    /// Returns the normal at a sample from the slope between its neighbours, so the surface looks smooth.
+    fn sample_normal(&self, i: usize, j: usize) -> Vector {
+        let (dx, dz) = self.spacing();
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(self.samples.0 - 1));
+        let (near, far) = (j.saturating_sub(1), (j + 1).min(self.samples.1 - 1));
+        let slope_x = (self.height(right, j) - self.height(left, j)) * self.size.y
+            / ((right - left) as f64 * dx);
+        let slope_z =
+            (self.height(i, far) - self.height(i, near)) * self.size.y / ((far - near) as f64 * dz);
+        Vector::make(-slope_x, 1., -slope_z).normalized()
+    }
+
+    /// Builds the hierarchy of ranges, starting with single cells and merging two by two blocks into one.
+    fn range_levels(&self) -> Vec<RangeLevel> {
+        let (columns, rows) = (self.samples.0 - 1, self.samples.1 - 1);
+        let y = |i, j| self.corner.y + self.height(i, j) * self.size.y;
+        let cells = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let corners = [y(i, j), y(i + 1, j), y(i, j + 1), y(i + 1, j + 1)];
+                let low = corners.iter().copied().fold(f64::INFINITY, f64::min);
+                let high = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (low, high)
+            })
+            .collect();
+        let mut levels = vec![RangeLevel {
+            columns,
+            rows,
+            ranges: cells,
+        }];
+
+        while let Some(last) = levels.last()
+            && (last.columns > 1 || last.rows > 1)
+        {
+            let (columns, rows) = (last.columns.div_ceil(2), last.rows.div_ceil(2));
+            let mut ranges = Vec::with_capacity(columns * rows);
+            for j in 0..rows {
+                for i in 0..columns {
+                    let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+                    for (ci, cj) in children(last, i, j) {
+                        let (low, high) = last.ranges[cj * last.columns + ci];
+                        range = (range.0.min(low), range.1.max(high));
+                    }
+                    ranges.push(range);
+                }
+            }
+            levels.push(RangeLevel {
+                columns,
+                rows,
+                ranges,
+            });
+        }
+        levels
+    }
+
+    /// Returns the distances at which 'ray' enters and exits the box around block ('i', 'j') of 'level',
+    /// if it passes through it between 't_min' and 't_max'.
+    fn block_span(
+        &self,
+        level: usize,
+        (i, j): (usize, usize),
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(f64, f64)> {
+        let (dx, dz) = self.spacing();
+        let cells = 1 << level;
+        let (low, high) = self.ranges[level].ranges[j * self.ranges[level].columns + i];
+        let columns = (
+            (i * cells) as f64,
+            (((i + 1) * cells).min(self.samples.0 - 1)) as f64,
+        );
+        let rows = (
+            (j * cells) as f64,
+            (((j + 1) * cells).min(self.samples.1 - 1)) as f64,
+        );
+        let min = Vector::make(
+            self.corner.x + columns.0 * dx,
+            low,
+            self.corner.z + rows.0 * dz,
+        );
+        let max = Vector::make(
+            self.corner.x + columns.1 * dx,
+            high,
+            self.corner.z + rows.1 * dz,
+        );
+
+        let (mut enter, mut exit) = (t_min, t_max);
+        for (origin, direction, low, high) in [
+            (ray.origin.x, ray.direction.x, min.x, max.x),
+            (ray.origin.y, ray.direction.y, min.y, max.y),
+            (ray.origin.z, ray.direction.z, min.z, max.z),
+        ] {
+            if direction.abs() < 1e-12 {
+                if origin < low || origin > high {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((low - origin) / direction, (high - origin) / direction);
+            enter = enter.max(a.min(b));
+            exit = exit.min(a.max(b));
+        }
+        // Flat blocks make boxes without height, so the comparison allows touching them
+        (enter <= exit).then_some((enter, exit))
+    }
+
+    /// Returns the closest hit within block ('i', 'j') of 'level', visiting the blocks below it from near to far
+    /// and skipping those whose box the ray misses.
+    fn hit_block(
+        &self,
+        level: usize,
+        block: (usize, usize),
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'_>> {
+        if level == 0 {
+            return self.hit_cell(block, ray, t_min, t_max);
+        }
+
+        let below = &self.ranges[level - 1];
+        let mut nearer: Vec<(f64, (usize, usize))> = children(below, block.0, block.1)
+            .filter_map(|child| {
+                let (enter, _) = self.block_span(level - 1, child, ray, t_min, t_max)?;
+                Some((enter, child))
+            })
+            .collect();
+        nearer.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut closest = None;
+        let mut t_max = t_max;
+        for (enter, child) in nearer {
+            if enter >= t_max {
+                break;
+            }
+            if let Some(hit) = self.hit_block(level - 1, child, ray, t_min, t_max) {
+                t_max = hit.t;
+                closest = Some(hit);
+            }
+        }
+        closest
+    }
+
+    /// Returns the closest hit with the two triangles of the cell between samples ('i', 'j') and ('i' + 1, 'j' + 1).
+    fn hit_cell(
+        &self,
+        (i, j): (usize, usize),
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'_>> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut closest: Option<(f64, Vector)> = None;
+        let mut t_max = t_max;
+        for triangle in [[0, 2, 1], [0, 3, 2]] {
+            let [a, b, c] = triangle.map(|k| corners[k]);
+            let vertices = (
+                self.vertex(a.0, a.1),
+                self.vertex(b.0, b.1),
+                self.vertex(c.0, c.1),
+            );
+            if let Some((t, u, v)) = hit_triangle(vertices, ray, t_min, t_max) {
+                let normal = |(i, j): (usize, usize)| self.normals[j * self.samples.0 + i];
+                let smooth = normal(a) * (1. - u - v) + normal(b) * u + normal(c) * v;
+                t_max = t;
+                closest = Some((t, smooth.normalized()));
+            }
+        }
+
+        let (t, normal) = closest?;
+        let point = ray.origin + ray.direction * t;
+        let uv = (
+            (point.x - self.corner.x) / self.size.x,
+            (point.z - self.corner.z) / self.size.z,
+        );
+        // u grows along x, which is tilted to follow the slope
+        let along = Vector::make(1., 0., 0.);
+        let tangent = (along - normal * normal.dot(&along)).normalized();
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            tangent,
+            uv,
+            self.size.x.max(self.size.z),
+            &self.material,
+        ))
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let top = self.ranges.len() - 1;
+        self.block_span(top, (0, 0), ray, t_min, t_max)?;
+        self.hit_block(top, (0, 0), ray, t_min, t_max)
+    }
+}
+
+/// Returns the blocks of 'level' that make up block ('i', 'j') of the level above it.
+fn children(level: &RangeLevel, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
+    let (columns, rows) = (level.columns, level.rows);
+    [(0, 0), (1, 0), (0, 1), (1, 1)]
+        .into_iter()
+        .map(move |(di, dj)| (2 * i + di, 2 * j + dj))
+        .filter(move |&(ci, cj)| ci < columns && cj < rows)
+}
@@ -5,10 +5,25 @@ use std::time::Duration;
 mod vectors;
 use vectors::Vector;
 
+mod assets;
+
 mod scene;
+use scene::Scene;
+
+mod graph;
+
+mod csg;
+
+mod shapes;
+
+mod sdf;
+
+mod heightfield;
+
+mod procedural;
 
 mod camera;
-use camera::Camera;
+use camera::{Camera, Projection, Stereo, StereoMode};
 
 mod tests;
 
@@ -17,6 +32,11 @@ const ASPECT_RATIO: (u16, u16) = (16, 10);
 const GLOBINA: u32 = 5;
 
 fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(output) = argument(&args, "--output") {
+        return render_to_file(&args, output);
+    }
+
     let sdl_context = sdl2::init()?;
     let video = sdl_context.video()?;
     let (x_ratio, y_ratio) = ASPECT_RATIO;
@@ -28,7 +48,9 @@ fn main() -> Result<(), String> {
         .map_err(|e| e.to_string())?;
     let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
 
-    let mut scene = &tests::SCENE1;
+    // The orrery is owned here rather than static, so its planets can be moved between frames
+    let mut orrery = tests::orrery();
+    let mut scene: &Scene = &tests::SCENE1;
 
     let mut camera = Camera::new(Vector::make(-3.0, 0.0, 0.0), Vector::make(1.0, 0.0, 0.0));
 
@@ -38,7 +60,7 @@ fn main() -> Result<(), String> {
     let mut event_pump = sdl_context.event_pump()?;
     'running: loop {
         for event in event_pump.poll_iter() {
-            if let Event::Quit{ .. } = event {
+            if let Event::Quit { .. } = event {
                 break 'running;
             }
             if let Event::KeyDown {
@@ -54,9 +76,26 @@ fn main() -> Result<(), String> {
                     Keycode::Num4 => scene = &tests::SCENE4,
                     Keycode::Num5 => scene = &tests::SCENE5,
                     Keycode::Num6 => scene = &tests::SCENE6,
+                    Keycode::Num7 => scene = &tests::SCENE7,
+                    Keycode::Num8 => scene = &tests::SCENE8,
+                    Keycode::Num9 => scene = &tests::SCENE9,
+                    Keycode::G => scene = &tests::SCENE_G,
                     Keycode::H => scene = &tests::SCENE_H,
+                    Keycode::I => scene = &tests::SCENE_I,
                     Keycode::J => scene = &tests::SCENE_J,
                     Keycode::K => scene = &tests::PEAK_K,
+                    Keycode::L => scene = &tests::SCENE_L,
+                    Keycode::O => {
+                        // Pressing the key again while looking at the orrery moves it along
+                        if std::ptr::eq(scene, &orrery) {
+                            tests::advance_orrery(&mut orrery, 1.);
+                        }
+                        scene = &orrery;
+                    }
+                    Keycode::R => scene = &tests::SCENE_R,
+                    Keycode::T => scene = &tests::SCENE_T,
+                    Keycode::U => scene = &tests::SCENE_U,
+                    Keycode::Y => scene = &tests::SCENE_Y,
 
                     Keycode::W
                     | Keycode::A
@@ -78,6 +117,27 @@ fn main() -> Result<(), String> {
                     Keycode::V => {
                         camera = camera.reset_location_reversed();
                     }
+                    Keycode::Z => {
+                        camera = camera.adjust_aperture(-0.02);
+                    }
+                    Keycode::X => {
+                        camera = camera.adjust_aperture(0.02);
+                    }
+                    Keycode::F => {
+                        camera = camera.autofocus(scene);
+                    }
+                    Keycode::B => {
+                        camera = camera.toggle_aperture_shape();
+                    }
+                    Keycode::M => {
+                        camera = camera.toggle_shutter();
+                    }
+                    Keycode::P => {
+                        camera = camera.next_projection();
+                    }
+                    Keycode::N => {
+                        camera = camera.next_stereo_mode();
+                    }
                     _ => {}
                 }
 
@@ -91,3 +151,81 @@ fn main() -> Result<(), String> {
 
     Ok(())
 }
+
+/// Renders a scene straight into an image file without opening a window.
+/// Usage: ray_tracing --output <file.png> [--scene <key>] [--projection <name>] [--stereo <mode>] [--width <pixels>]
+fn render_to_file(args: &[String], output: &str) -> Result<(), String> {
+    let scene = match argument(args, "--scene") {
+        Some(name) => scene_by_name(name).ok_or(format!("Unknown scene '{}'", name))?,
+        None => &tests::SCENE1,
+    };
+    let projection = match argument(args, "--projection") {
+        Some(name) => {
+            Projection::from_name(name).ok_or(format!("Unknown projection '{}'", name))?
+        }
+        None => Projection::Perspective,
+    };
+    let stereo = match argument(args, "--stereo") {
+        Some(name) => Some(Stereo {
+            mode: StereoMode::from_name(name).ok_or(format!("Unknown stereo mode '{}'", name))?,
+            interocular: 0.1,
+            convergence: 3.,
+        }),
+        None => None,
+    };
+    let width = match argument(args, "--width") {
+        Some(width) => width.parse::<u16>().map_err(|e| e.to_string())?,
+        None => WIDTH,
+    };
+
+    let camera = Camera {
+        projection,
+        stereo,
+        ..Camera::new(Vector::make(-3.0, 0.0, 0.0), Vector::make(1.0, 0.0, 0.0))
+    };
+    // Panorama viewers expect equirectangular images with a 2:1 aspect ratio for every eye
+    let ratio = match (projection, stereo.map(|stereo| stereo.mode)) {
+        (Projection::Equirectangular, Some(StereoMode::TopBottom)) => (1, 1),
+        (Projection::Equirectangular, _) => (2, 1),
+        _ => ASPECT_RATIO,
+    };
+
+    camera
+        .render(scene, width, ratio, GLOBINA)
+        .save(output)
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the value following the command line option 'name', if it was given.
+fn argument<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    args.get(position + 1).map(|value| value.as_str())
+}
+
+/// Returns the scene selected with the given key in the viewer.
+fn scene_by_name(name: &str) -> Option<&'static Scene> {
+    let scene: &'static Scene = match name.to_uppercase().as_str() {
+        "1" => &tests::SCENE1,
+        "2" => &tests::SCENE2,
+        "3" => &tests::SCENE3,
+        "4" => &tests::SCENE4,
+        "5" => &tests::SCENE5,
+        "6" => &tests::SCENE6,
+        "7" => &tests::SCENE7,
+        "8" => &tests::SCENE8,
+        "9" => &tests::SCENE9,
+        "G" => &tests::SCENE_G,
+        "H" => &tests::SCENE_H,
+        "I" => &tests::SCENE_I,
+        "J" => &tests::SCENE_J,
+        "K" => &tests::PEAK_K,
+        "L" => &tests::SCENE_L,
+        "O" => &tests::SCENE_O,
+        "R" => &tests::SCENE_R,
+        "T" => &tests::SCENE_T,
+        "U" => &tests::SCENE_U,
+        "Y" => &tests::SCENE_Y,
+        _ => return None,
+    };
+    Some(scene)
+}
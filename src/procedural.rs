@@ -0,0 +1,234 @@
+use crate::scene::srgb_to_linear;
+use crate::vectors::Vector;
+use sdl2::pixels::Color;
+use std::f64::consts::TAU;
+
+/// A pattern that colors a surface without an image file, blending between two colors.
+pub struct Procedural {
+    pub pattern: Pattern,
+    pub colors: (Color, Color),
+    pub scale: f64, // size of one repetition of the pattern
+    pub space: PatternSpace,
+}
+
+#[derive(Clone, Copy)]
+pub enum Pattern {
+    Checkerboard,
+    Stripes,         // across the x axis, or across u in UV space
+    Gradient,        // from the first color at y = 0 to the second at y = 1, or along v in UV space
+    Noise,           // smooth Perlin noise
+    Turbulence(u32), // sum of the given number of octaves of noise, each twice as fine as the last
+    Marble,          // stripes distorted by turbulence
+    Wood,            // rings around the y axis distorted by noise
+    Worley,          // cells around randomly placed points, with the first color at the points
+}
+
+#[derive(Clone, Copy)]
+pub enum PatternSpace {
+    Solid, // the pattern fills the space, so objects look carved out of it
+    Uv,    // the pattern is laid over the UV coordinates of the surface, as the point (u, v, 0)
+}
+
+impl Procedural {
+    pub fn make(pattern: Pattern, colors: (Color, Color), scale: f64, space: PatternSpace) -> Self {
+        Procedural {
+            pattern,
+            colors,
+            scale,
+            space,
+        }
+    }
+
+    /// Returns the linear color of the pattern at 'point', or at 'uv' for patterns in UV space.
+    pub fn color(&self, point: Vector, uv: (f64, f64)) -> Vector {
+        let p = match self.space {
+            PatternSpace::Solid => point,
+            PatternSpace::Uv => Vector::make(uv.0, uv.1, 0.),
+        } / self.scale;
+        let t = self.pattern.value(p).clamp(0., 1.);
+        linear(self.colors.0) * (1. - t) + linear(self.colors.1) * t
+    }
+}
+
+/// Colors a surface by its height and steepness, like the grass, rock and snow of a landscape.
+pub struct ColorMap {
+    pub heights: Vec<(f64, Color)>, // colors at increasing heights, blended in between
+    pub steep: Option<(f64, Color)>, // color of slopes steeper than the given angle in radians
+}
+
+impl ColorMap {
+    pub fn make(heights: Vec<(f64, Color)>) -> Self {
+        ColorMap {
+            heights,
+            steep: None,
+        }
+    }
+
+    pub fn with_steep(self, angle: f64, color: Color) -> Self {
+        ColorMap {
+            steep: Some((angle, color)),
+            ..self
+        }
+    }
+
+    /// Returns the linear color at 'point' of a surface with the given 'normal'.
+    pub fn color(&self, point: Vector, normal: Vector) -> Vector {
+        let y = point.y;
+        let above = self.heights.iter().position(|&(height, _)| height > y);
+        let color = match above {
+            None => self
+                .heights
+                .last()
+                .map_or(Vector::make(0., 0., 0.), |&(_, c)| linear(c)),
+            Some(0) => linear(self.heights[0].1),
+            Some(i) => {
+                let ((low, below), (high, above)) = (self.heights[i - 1], self.heights[i]);
+                let t = if high > low {
+                    (y - low) / (high - low)
+                } else {
+                    1.
+                };
+                linear(below) * (1. - t) + linear(above) * t
+            }
+        };
+
+        match self.steep {
+            Some((angle, steep)) => {
+                // Slopes blend into the steep color over a few degrees around the angle
+                let slope = normal.y.clamp(-1., 1.).acos();
+                let t = ((slope - angle) / 0.2 + 0.5).clamp(0., 1.);
+                let t = t * t * (3. - 2. * t);
+                color * (1. - t) + linear(steep) * t
+            }
+            None => color,
+        }
+    }
+}
+
+/// Returns a color in linear light.
+fn linear(color: Color) -> Vector {
+    Vector::make(
+        srgb_to_linear(color.r),
+        srgb_to_linear(color.g),
+        srgb_to_linear(color.b),
+    )
+}
+
+impl Pattern {
+    /// Returns the value of the pattern at 'p', 0.0 for the first color and 1.0 for the second.
+    pub fn value(&self, p: Vector) -> f64 {
+        // Surfaces lying exactly on a cell boundary flicker between the colors, so offset them slightly
+        let q = p + Vector::make(1e-6, 1e-6, 1e-6);
+        match self {
+            Pattern::Checkerboard => parity(q.x.floor() + q.y.floor() + q.z.floor()),
+            Pattern::Stripes => parity(q.x.floor()),
+            Pattern::Gradient => p.y,
+            Pattern::Noise => 0.5 + 0.5 * perlin(p),
+            Pattern::Turbulence(octaves) => turbulence(p, *octaves),
+            Pattern::Marble => 0.5 + 0.5 * (TAU * (p.x + turbulence(p, 6))).sin(),
+            Pattern::Wood => (p.x.hypot(p.z) * 4. + 0.5 * perlin(p * 2.)).rem_euclid(1.),
+            Pattern::Worley => worley(p),
+        }
+    }
+}
+
+/// Returns 0.0 for even and 1.0 for odd whole numbers.
+fn parity(value: f64) -> f64 {
+    value.rem_euclid(2.)
+}
+
+/// Ken Perlin's improved gradient noise, between about -1.0 and 1.0.
+fn perlin(p: Vector) -> f64 {
+    let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (x, y, z) = (p.x - x0, p.y - y0, p.z - z0);
+    let (cx, cy, cz) = (x0 as i64, y0 as i64, z0 as i64);
+
+    // Every corner of the cell contributes its gradient, blended with a smooth curve
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        gradient(
+            hash(cx + dx, cy + dy, cz + dz),
+            x - dx as f64,
+            y - dy as f64,
+            z - dz as f64,
+        )
+    };
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+    let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+            lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+        ),
+        lerp(
+            v,
+            lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+            lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+        ),
+    )
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+/// Returns the dot product of (x, y, z) with one of the twelve gradient directions of Perlin noise.
+fn gradient(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+/// Returns a pseudo random number for the lattice point (x, y, z).
+fn hash(x: i64, y: i64, z: i64) -> u64 {
+    let mut h = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+    h ^= h >> 29;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^ (h >> 32)
+}
+
+/// Sums the absolute value of 'octaves' layers of noise, each twice as fine and half as strong as the last.
+fn turbulence(p: Vector, octaves: u32) -> f64 {
+    (0..octaves)
+        .map(|octave| {
+            let frequency = 2f64.powi(octave as i32);
+            perlin(p * frequency).abs() / frequency
+        })
+        .sum()
+}
+
+/// Returns the distance from 'p' to the closest of the points scattered one per unit cell.
+fn worley(p: Vector) -> f64 {
+    let (cx, cy, cz) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+    let mut closest = f64::INFINITY;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                let (x, y, z) = (cx + dx, cy + dy, cz + dz);
+                let h = hash(x, y, z);
+                let fraction = |shift: u32| ((h >> shift) & 0xFFFF) as f64 / 65536.;
+                let point = Vector::make(
+                    x as f64 + fraction(0),
+                    y as f64 + fraction(16),
+                    z as f64 + fraction(32),
+                );
+                closest = closest.min((point - p).length());
+            }
+        }
+    }
+    closest
+}
@@ -1,9 +1,14 @@
+use crate::assets::{self, AssetError};
 use crate::camera::Ray;
-use crate::vectors::Vector;
-use image::RgbImage;
+use crate::graph::Node;
+use crate::procedural::{ColorMap, Procedural};
+use crate::vectors::{Matrix4, Vector};
+use image::{Rgba, RgbaImage};
 use sdl2::pixels::Color;
+use std::sync::Arc;
 pub struct Scene {
     pub objects: Vec<Box<dyn Hittable>>,
+    pub nodes: Vec<Node>, // named parts of the scene, which can be looked up and changed
     pub lights: Vec<Light>,
     pub ambient_light: f64,
 }
@@ -12,64 +17,485 @@ impl Scene {
     pub fn make(objects: Vec<Box<dyn Hittable>>, lights: Vec<Light>, ambient_light: f64) -> Scene {
         Scene {
             objects,
+            nodes: Vec::new(),
             lights,
             ambient_light,
         }
     }
+
+    pub fn with_nodes(self, nodes: Vec<Node>) -> Self {
+        Scene { nodes, ..self }
+    }
+
+    /// Returns the node with the given name anywhere in the scene graph, so it can be moved, hidden or recolored.
+    pub fn node_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.nodes.iter_mut().find_map(|node| node.find_mut(name))
+    }
 }
 
 pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray) -> Option<f64>;
-    fn normal(&self, point: Vector) -> Vector;
-    fn material(&self) -> &Material;
+    /// Returns the closest intersection of 'ray' with the object whose distance lies between 't_min' and 't_max'.
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>>;
+
+    /// Returns every stretch of the whole line through 'ray', including behind its origin, that lies inside
+    /// the object, sorted by distance. Objects without an inside, like planes, have none.
+    fn intervals(&self, _ray: &Ray) -> Vec<Interval<'_>> {
+        Vec::new()
+    }
+}
+
+/// The part of a ray between the hits where it enters and exits a solid object.
+pub struct Interval<'a> {
+    pub enter: HitRecord<'a>,
+    pub exit: HitRecord<'a>,
+}
+
+/// Everything the renderer needs to know about the point where a ray hit an object.
+pub struct HitRecord<'a> {
+    pub t: f64, // distance along the ray
+    pub point: Vector,
+    pub geometric_normal: Vector, // outward normal of the actual surface
+    pub normal: Vector,           // outward normal used for shading, tilted by bump and normal maps
+    pub tangent: Vector, // direction in which u grows along the surface, perpendicular to the normal
+    pub uv: (f64, f64),  // texture coordinates, between 0.0 and 1.0 across one copy of a texture
+    pub footprint: f64,  // width of the ray's footprint on the surface in UV units
+    pub front_face: bool, // whether the ray hit the outside of the surface
+    pub material: &'a Material,
+}
+
+impl<'a> HitRecord<'a> {
+    /// Makes a hit at distance 't' along 'ray' on a surface with the given outward normal and tangent,
+    /// where one unit of UV spans about 'uv_size' in the scene. The material's bump map tilts the shading normal.
+    pub fn new(
+        ray: &Ray,
+        t: f64,
+        outward_normal: Vector,
+        tangent: Vector,
+        uv: (f64, f64),
+        uv_size: f64,
+        material: &'a Material,
+    ) -> Self {
+        // The footprint stretches as the surface turns away from the ray
+        let cos = ray.direction.dot(&outward_normal).abs().max(0.05);
+        let mut hit = HitRecord {
+            t,
+            point: ray.origin + ray.direction * t,
+            geometric_normal: outward_normal,
+            normal: outward_normal,
+            tangent,
+            uv,
+            footprint: ray.footprint(t) / cos / uv_size,
+            front_face: ray.direction.dot(&outward_normal) < 0.,
+            material,
+        };
+        if let Some(bump) = &material.bump {
+            hit.normal = bump.shading_normal(&hit);
+        }
+        hit
+    }
+
+    /// Returns the direction in which v grows along the surface.
+    pub fn bitangent(&self) -> Vector {
+        self.normal.cross(&self.tangent)
+    }
+
+    /// Replaces the material of the hit, applying the bumps of the new material to the actual surface.
+    pub fn set_material(&mut self, material: &'a Material) {
+        self.material = material;
+        self.normal = match &material.bump {
+            Some(bump) => bump.shading_normal(&HitRecord {
+                normal: self.geometric_normal,
+                material,
+                ..*self
+            }),
+            None => self.geometric_normal,
+        };
+    }
+
+    /// Returns the hit as seen from the other side of the surface, with the normals pointing the other way.
+    pub fn flipped(self) -> Self {
+        HitRecord {
+            geometric_normal: -self.geometric_normal,
+            normal: -self.normal,
+            front_face: !self.front_face,
+            ..self
+        }
+    }
+
+    /// Returns how much of the light coming from behind the surface it blocks, between 0.0 and 1.0.
+    pub fn opacity(&self) -> f64 {
+        let alpha = match &self.material.color {
+            ColorType::Texture(texture) => texture.alpha(self.uv.0, self.uv.1, self.footprint),
+            _ => 1.,
+        };
+        self.material.opacity.unwrap_or(1.) * alpha
+    }
 }
 
 pub struct Material {
     pub color: ColorType,
     pub reflectivity: Option<f64>,
     pub transparency: Option<(f64, f64)>,
+    pub roughness: Option<f64>, // spreads reflections, 0.0 is a perfect mirror
+    pub absorption: Option<(Color, f64)>, // (tint the light takes on, density) inside a transparent object
+    pub dispersion: Option<f64>, // Abbe number of a transparent object, lower values split colors more
+    pub pbr: Option<Pbr>, // when set, replaces the options above and uses 'color' as the base color
+    pub bump: Option<Bump>, // small bumps and dents that only change the shading normal
+    pub opacity: Option<f64>, // between 0.0 (invisible) and 1.0, multiplied by the alpha channel of a texture
+}
+
+/// Detail that tilts the shading normal of a surface without changing its shape.
+pub enum Bump {
+    NormalMap(Arc<Texture>), // tangent-space normals, loaded with 'Texture::data_from_file'
+    HeightMap(Arc<Texture>, f64), // grayscale heights and how far a change of height between pixels tilts the normal
+}
+
+impl Bump {
+    /// Returns the shading normal at 'hit', whose 'normal' and 'tangent' are those of the actual surface.
+    pub fn shading_normal(&self, hit: &HitRecord) -> Vector {
+        let (u, v) = hit.uv;
+        let (normal, tangent, bitangent) = (hit.normal, hit.tangent, hit.bitangent());
+        let tilted = match self {
+            Bump::NormalMap(texture) => {
+                let n = texture.sample(u, v, hit.footprint) * 2. - Vector::make(1., 1., 1.);
+                tangent * n.x + bitangent * n.y + normal * n.z
+            }
+            Bump::HeightMap(texture, strength) => {
+                // Heights are compared a pixel (or the footprint, if it's larger) to either side
+                let pixel = 1. / texture.dimensions().0 as f64;
+                let step = pixel.max(hit.footprint);
+                let height = |u: f64, v: f64| {
+                    let h = texture.sample(u, v, hit.footprint);
+                    (h.x + h.y + h.z) / 3.
+                };
+                let du = (height(u + step, v) - height(u - step, v)) / (2. * step) * pixel;
+                let dv = (height(u, v + step) - height(u, v - step)) / (2. * step) * pixel;
+                normal - (tangent * du + bitangent * dv) * *strength
+            }
+        };
+        if tilted.length() > 0. {
+            tilted.normalized()
+        } else {
+            normal
+        }
+    }
+}
+
+/// Metallic/roughness parameters of a physically based material.
+pub struct Pbr {
+    pub metallic: f64,                 // between 0.0 (dielectric) and 1.0 (metal)
+    pub roughness: f64,                // perceptual roughness between 0.0 and 1.0
+    pub ior: f64,                      // index of refraction of the dielectric part
+    pub clearcoat: Option<(f64, f64)>, // (strength, roughness) of a thin glossy layer on top
+}
+
+impl Pbr {
+    /// Returns the reflectance at normal incidence for a surface with the given 'base_color' (linear RGB).
+    pub fn f0(&self, base_color: Vector) -> Vector {
+        let dielectric = ((self.ior - 1.) / (self.ior + 1.)).powi(2);
+        let dielectric = Vector::make(dielectric, dielectric, dielectric);
+        dielectric * (1. - self.metallic) + base_color * self.metallic
+    }
+
+    /// Returns the outgoing radiance per unit of light intensity and cosine, for view direction 'v',
+    /// light direction 'l' and surface 'normal'. The diffuse part is scaled by PI so a white
+    /// rough dielectric matches the Lambertian shading of the other materials.
+    pub fn brdf(&self, base_color: Vector, normal: Vector, v: Vector, l: Vector) -> Vector {
+        let n_dot_l = normal.dot(&l);
+        let n_dot_v = normal.dot(&v);
+        if n_dot_l <= 0. || n_dot_v <= 0. {
+            return Vector::make(0., 0., 0.);
+        }
+        let h = (v + l).normalized();
+        let n_dot_h = normal.dot(&h).max(0.);
+        let v_dot_h = v.dot(&h).max(0.);
+
+        let alpha = self.roughness.clamp(0.02, 1.).powi(2);
+        let fresnel = fresnel_schlick(self.f0(base_color), v_dot_h);
+        let specular = fresnel
+            * (ggx_distribution(n_dot_h, alpha) * smith_ggx(n_dot_v, n_dot_l, alpha)
+                / (4. * n_dot_v * n_dot_l));
+
+        // Energy that is reflected specularly is not available for diffuse scattering
+        let kd = (Vector::make(1., 1., 1.) - fresnel) * (1. - self.metallic);
+        let mut result = kd.multiply(&base_color) + specular * std::f64::consts::PI;
+
+        if let Some((strength, coat_roughness)) = self.clearcoat {
+            let coat_alpha = coat_roughness.clamp(0.02, 1.).powi(2);
+            let coat_fresnel = fresnel_schlick_scalar(0.04, v_dot_h) * strength;
+            let coat = coat_fresnel
+                * ggx_distribution(n_dot_h, coat_alpha)
+                * smith_ggx(n_dot_v, n_dot_l, coat_alpha)
+                / (4. * n_dot_v * n_dot_l);
+            result = result * (1. - coat_fresnel)
+                + Vector::make(coat, coat, coat) * std::f64::consts::PI;
+        }
+
+        result
+    }
+
+    /// Returns the fraction of the light from the mirror direction that is reflected towards the viewer,
+    /// with rough surfaces reflecting less at grazing angles.
+    pub fn environment_fresnel(&self, base_color: Vector, n_dot_v: f64) -> Vector {
+        let f0 = self.f0(base_color);
+        let smooth = 1. - self.roughness;
+        let max = Vector::make(smooth.max(f0.x), smooth.max(f0.y), smooth.max(f0.z));
+        let factor = (1. - n_dot_v).clamp(0., 1.).powi(5);
+        let mut fresnel = f0 + (max - f0) * factor;
+
+        if let Some((strength, _)) = self.clearcoat {
+            let coat = fresnel_schlick_scalar(0.04, n_dot_v) * strength;
+            fresnel = fresnel * (1. - coat) + Vector::make(coat, coat, coat);
+        }
+        fresnel
+    }
+}
+
+/// GGX (Trowbridge-Reitz) normal distribution function.
+fn ggx_distribution(n_dot_h: f64, alpha: f64) -> f64 {
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.) + 1.;
+    alpha2 / (std::f64::consts::PI * d * d)
+}
+
+/// Smith masking-shadowing term with the Schlick-GGX approximation.
+fn smith_ggx(n_dot_v: f64, n_dot_l: f64, alpha: f64) -> f64 {
+    let k = alpha / 2.;
+    let g1 = |n_dot_x: f64| n_dot_x / (n_dot_x * (1. - k) + k);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+/// Schlick's approximation of the Fresnel reflectance.
+fn fresnel_schlick(f0: Vector, cos_theta: f64) -> Vector {
+    let factor = (1. - cos_theta).clamp(0., 1.).powi(5);
+    f0 + (Vector::make(1., 1., 1.) - f0) * factor
+}
+
+fn fresnel_schlick_scalar(f0: f64, cos_theta: f64) -> f64 {
+    f0 + (1. - f0) * (1. - cos_theta).clamp(0., 1.).powi(5)
 }
 
 pub enum ColorType {
     Solid(Color),
     Function(Box<dyn Fn(Vector) -> Color + Send + Sync>), // Send and Sync are safety features as LazyLock is used
+    Texture(Arc<Texture>), // sampled at the UV coordinates of the hit
+    Procedural(Procedural),
+    Terrain(ColorMap), // by the height and steepness of the surface
+}
+
+/// Converts an sRGB encoded color channel to linear light between 0.0 and 1.0.
+pub fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes linear light between 0.0 and 1.0 as an sRGB color channel.
+pub fn linear_to_srgb(value: f64) -> u8 {
+    let c = value.clamp(0., 1.);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    };
+    (c * 255.).round() as u8
 }
 
 pub struct Light {
     pub position: Vector,
-    pub intensity: f64, // between 0.0 and 1.0
+    pub intensity: f64,                 // between 0.0 and 1.0
+    pub area: Option<(Vector, Vector)>, // edges of a rectangle spanned from 'position', None for a point light
 }
 
-pub struct Collision<'a> {
-    pub object: &'a dyn Hittable,
-    pub point: Vector,
+pub struct Texture {
+    mipmaps: Arc<Mipmaps>, // shared by all textures loaded from the same file
+    pub filter: Filter,
+    pub wrap: (WrapMode, WrapMode), // along u and v
 }
 
-pub struct Texture {
-    image: RgbImage,
-    width: u32,
-    height: u32,
+/// The pixels of an image together with its mipmaps.
+pub struct Mipmaps {
+    levels: Vec<RgbaImage>, // each level half the size of the previous one
+    data: bool, // whether the pixels hold data such as normals, which are stored linearly rather than as sRGB colors
+}
+
+impl Mipmaps {
+    pub fn make(image: RgbaImage, data: bool) -> Self {
+        let mut levels = vec![image];
+        while let Some(last) = levels.last()
+            && (last.width() > 1 || last.height() > 1)
+        {
+            levels.push(downsample(last, data));
+        }
+        Mipmaps { levels, data }
+    }
+
+    /// Returns the largest level that is at most 'size' pixels wide, or the smallest one if all are wider.
+    pub fn level(&self, size: u32) -> &RgbaImage {
+        self.levels
+            .iter()
+            .find(|level| level.width() <= size)
+            .or(self.levels.last())
+            .expect("Mipmaps have at least one level")
+    }
+}
+
+/// How a texture is sampled between and across its pixels.
+#[derive(Clone, Copy)]
+pub enum Filter {
+    Bilinear,  // blends the four closest pixels of the full size image
+    Trilinear, // also blends the two mipmaps closest to the size of the ray's footprint
+}
+
+/// What a texture shows outside of the UV range from 0.0 to 1.0.
+#[derive(Clone, Copy)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,  // repeats the edge pixels
+    Mirror, // repeats the texture, flipping every other copy
 }
 
 impl Texture {
-    pub fn from_file(path: &str) -> Texture {
-        let img = image::open(path).expect("Failed to load texture").to_rgb8();
-        let (w, h) = img.dimensions();
+    /// Loads an image of colors encoded in sRGB, like photos and painted textures.
+    /// The path is resolved with 'assets::resolve' and each file is only decoded once.
+    pub fn from_file(path: &str) -> Result<Texture, AssetError> {
+        Texture::load(path, false)
+    }
+
+    /// Loads an image like 'from_file', or reports why it can't and returns the placeholder instead.
+    pub fn from_file_or_placeholder(path: &str) -> Texture {
+        Texture::from_file(path).unwrap_or_else(|error| {
+            eprintln!("{error}, using a placeholder texture");
+            Texture::placeholder()
+        })
+    }
+
+    /// Loads an image that holds data such as normals or heights, which is used as stored rather than decoded from sRGB.
+    pub fn data_from_file(path: &str) -> Result<Texture, AssetError> {
+        Texture::load(path, true)
+    }
+
+    /// Loads a data image like 'data_from_file', or reports why it can't and returns the placeholder instead.
+    pub fn data_from_file_or_placeholder(path: &str) -> Texture {
+        Texture::data_from_file(path).unwrap_or_else(|error| {
+            eprintln!("{error}, using a placeholder texture");
+            Texture::placeholder()
+        })
+    }
+
+    fn load(path: &str, data: bool) -> Result<Texture, AssetError> {
+        Ok(Texture::from_mipmaps(assets::mipmaps(path, data)?))
+    }
+
+    /// Returns a magenta and black checkerboard that stands in for textures that could not be loaded.
+    pub fn placeholder() -> Texture {
+        let image = RgbaImage::from_fn(64, 64, |x, y| {
+            if (x / 8 + y / 8) % 2 == 0 {
+                Rgba([255, 0, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        });
+        Texture::from_mipmaps(Arc::new(Mipmaps::make(image, false)))
+    }
+
+    fn from_mipmaps(mipmaps: Arc<Mipmaps>) -> Texture {
         Texture {
-            image: img,
-            width: w,
-            height: h,
+            mipmaps,
+            filter: Filter::Trilinear,
+            wrap: (WrapMode::Repeat, WrapMode::Repeat),
         }
     }
 
-    pub fn uv_pixel_from_texture(&self, u: f64, v: f64) -> Color {
-        let x = (u.fract() * self.width as f64) as u32;
-        let y = ((1.0 - v.fract()) * self.height as f64) as u32;
-        let pixel = self
-            .image
-            .get_pixel(x.min(self.width - 1), y.min(self.height - 1));
-        Color::RGB(pixel[0], pixel[1], pixel[2])
+    pub fn with_filter(self, filter: Filter) -> Self {
+        Texture { filter, ..self }
     }
+
+    pub fn with_wrap(self, u: WrapMode, v: WrapMode) -> Self {
+        Texture {
+            wrap: (u, v),
+            ..self
+        }
+    }
+
+    /// Returns the width and height of the full size image in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.mipmaps.levels[0].dimensions()
+    }
+
+    /// Returns the linear color (or the data) of the texture at (u, v), with channels between 0.0 and 1.0,
+    /// for a ray whose footprint is 'footprint' wide in UV units.
+    pub fn sample(&self, u: f64, v: f64, footprint: f64) -> Vector {
+        self.filtered(u, v, footprint).0
+    }
+
+    /// Returns the alpha channel of the texture at (u, v) like 'sample', 1.0 for images without one.
+    pub fn alpha(&self, u: f64, v: f64, footprint: f64) -> f64 {
+        self.filtered(u, v, footprint).1
+    }
+
+    /// Returns the color and alpha at (u, v), filtered for the footprint of the ray.
+    fn filtered(&self, u: f64, v: f64, footprint: f64) -> (Vector, f64) {
+        match self.filter {
+            Filter::Bilinear => self.bilinear(0, u, v),
+            Filter::Trilinear => {
+                // The level whose pixels are about as large as the footprint
+                let levels = &self.mipmaps.levels;
+                let max_level = (levels.len() - 1) as f64;
+                let lod = (footprint * levels[0].width() as f64)
+                    .log2()
+                    .clamp(0., max_level);
+                let level = lod.floor() as usize;
+                let t = lod - level as f64;
+                if t > 0. {
+                    mix(
+                        self.bilinear(level, u, v),
+                        self.bilinear(level + 1, u, v),
+                        t,
+                    )
+                } else {
+                    self.bilinear(level, u, v)
+                }
+            }
+        }
+    }
+
+    /// Returns the color and alpha at (u, v) of the given mipmap level, blended between the four closest pixels.
+    fn bilinear(&self, level: usize, u: f64, v: f64) -> (Vector, f64) {
+        let image = &self.mipmaps.levels[level];
+        let x = u * image.width() as f64 - 0.5;
+        let y = (1.0 - v) * image.height() as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let pixel = |dx: i64, dy: i64| {
+            let px = wrap(x0 as i64 + dx, image.width(), self.wrap.0);
+            let py = wrap(y0 as i64 + dy, image.height(), self.wrap.1);
+            let pixel = image.get_pixel(px, py);
+            (self.decode(pixel), pixel[3] as f64 / 255.)
+        };
+        mix(
+            mix(pixel(0, 0), pixel(1, 0), tx),
+            mix(pixel(0, 1), pixel(1, 1), tx),
+            ty,
+        )
+    }
+
+    /// Converts the color channels of a stored pixel to values between 0.0 and 1.0, in linear space for colors.
+    fn decode(&self, pixel: &Rgba<u8>) -> Vector {
+        let channel = |value: u8| {
+            if self.mipmaps.data {
+                value as f64 / 255.
+            } else {
+                srgb_to_linear(value)
+            }
+        };
+        Vector::make(channel(pixel[0]), channel(pixel[1]), channel(pixel[2]))
+    }
+
     pub fn sphere_uv(center: Vector, radius: f64, point: Vector) -> (f64, f64) {
         let p = (point - center) / radius;
         let u = 0.5 - p.z.atan2(p.x) / (2.0 * std::f64::consts::PI);
@@ -78,6 +504,63 @@ impl Texture {
     }
 }
 
+/// Blends two (color, alpha) pairs, from 'a' at 't' = 0.0 to 'b' at 't' = 1.0.
+fn mix(a: (Vector, f64), b: (Vector, f64), t: f64) -> (Vector, f64) {
+    (a.0 * (1. - t) + b.0 * t, a.1 * (1. - t) + b.1 * t)
+}
+
+/// Returns an image half the size of 'image', averaging every 2x2 pixels in linear space unless it holds 'data'.
+/// Colors are weighted by their alpha, so fully transparent pixels don't darken the edges of opaque ones.
+fn downsample(image: &RgbaImage, data: bool) -> RgbaImage {
+    let (w, h) = ((image.width() / 2).max(1), (image.height() / 2).max(1));
+    RgbaImage::from_fn(w, h, |x, y| {
+        let mut sum = [0.; 3];
+        let mut alpha = 0.;
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let pixel = image.get_pixel(
+                (2 * x + dx).min(image.width() - 1),
+                (2 * y + dy).min(image.height() - 1),
+            );
+            let weight = (pixel[3] as f64 / 255.).max(1e-6);
+            for channel in 0..3 {
+                sum[channel] += if data {
+                    pixel[channel] as f64 / 255.
+                } else {
+                    srgb_to_linear(pixel[channel])
+                } * weight;
+            }
+            alpha += weight;
+        }
+        let [r, g, b] = sum.map(|value| {
+            let value = value / alpha;
+            if data {
+                (value * 255.).round() as u8
+            } else {
+                linear_to_srgb(value)
+            }
+        });
+        Rgba([r, g, b, (alpha / 4. * 255.).round() as u8])
+    })
+}
+
+/// Returns the pixel index for 'index' of a texture 'size' pixels long, following the wrap 'mode'.
+fn wrap(index: i64, size: u32, mode: WrapMode) -> u32 {
+    let size = size as i64;
+    let index = match mode {
+        WrapMode::Repeat => index.rem_euclid(size),
+        WrapMode::Clamp => index.clamp(0, size - 1),
+        WrapMode::Mirror => {
+            let index = index.rem_euclid(2 * size);
+            if index < size {
+                index
+            } else {
+                2 * size - 1 - index
+            }
+        }
+    };
+    index as u32
+}
+
 pub struct Sphere {
     pub center: Vector,
     pub radius: f64,
@@ -95,40 +578,305 @@ impl Sphere {
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray) -> Option<f64> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.dot(&ray.direction);
-        let b = 2.0 * oc.dot(&ray.direction);
-        let c = oc.dot(&oc) - self.radius * self.radius;
-        let discriminant = b * b - 4.0 * a * c;
-        if discriminant < 0.0 {
-            None
-        } else {
-            let root1 = (-b - discriminant.sqrt()) / (2.0 * a);
-            let root2 = (-b + discriminant.sqrt()) / (2.0 * a);
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center, self.radius, &self.material, ray, t_min, t_max)
+    }
 
-            if root1 > 0.001 {
-                Some(root1)
-            } else if root2 > 0.001 {
-                Some(root2)
-            } else {
-                None
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        sphere_intervals(self.center, self.radius, &self.material, ray)
+    }
+}
+
+/// Returns the closest intersection of 'ray' with the given sphere between 't_min' and 't_max'
+fn hit_sphere<'a>(
+    center: Vector,
+    radius: f64,
+    material: &'a Material,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'a>> {
+    let (root1, root2) = sphere_roots(center, radius, ray)?;
+    let t = if t_min < root1 && root1 < t_max {
+        root1
+    } else if t_min < root2 && root2 < t_max {
+        root2
+    } else {
+        return None;
+    };
+    Some(sphere_record(center, radius, material, ray, t))
+}
+
+/// Returns the inside of the given sphere along 'ray' as an interval, if the ray's line passes through it.
+fn sphere_intervals<'a>(
+    center: Vector,
+    radius: f64,
+    material: &'a Material,
+    ray: &Ray,
+) -> Vec<Interval<'a>> {
+    match sphere_roots(center, radius, ray) {
+        Some((root1, root2)) => vec![Interval {
+            enter: sphere_record(center, radius, material, ray, root1),
+            exit: sphere_record(center, radius, material, ray, root2),
+        }],
+        None => Vec::new(),
+    }
+}
+
+/// Returns the distances along 'ray' to where its line enters and exits the given sphere, if it passes through it.
+pub fn sphere_roots(center: Vector, radius: f64, ray: &Ray) -> Option<(f64, f64)> {
+    let oc = ray.origin - center;
+    let a = ray.direction.dot(&ray.direction);
+    let b = 2.0 * oc.dot(&ray.direction);
+    let c = oc.dot(&oc) - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        None
+    } else {
+        let root1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let root2 = (-b + discriminant.sqrt()) / (2.0 * a);
+        Some((root1, root2))
+    }
+}
+
+/// Makes the hit at distance 't' along 'ray' on the surface of the given sphere.
+fn sphere_record<'a>(
+    center: Vector,
+    radius: f64,
+    material: &'a Material,
+    ray: &Ray,
+    t: f64,
+) -> HitRecord<'a> {
+    let point = ray.origin + ray.direction * t;
+    let normal = (point - center) / radius;
+    let uv = Texture::sphere_uv(center, radius, point);
+    // u grows clockwise around the y axis when seen from above, and at the poles any direction will do
+    let tangent = Vector::make(normal.z, 0., -normal.x);
+    let tangent = if tangent.length() > 1e-9 {
+        tangent.normalized()
+    } else {
+        normal.orthonormal_basis().0
+    };
+    HitRecord::new(
+        ray,
+        t,
+        normal,
+        tangent,
+        uv,
+        std::f64::consts::TAU * radius,
+        material,
+    )
+}
+
+/// A sphere whose center moves through the given keyframes, linearly in between them.
+pub struct MovingSphere {
+    motion: Moving, // a sphere around the origin, moved to the center
+}
+
+impl MovingSphere {
+    /// Makes a sphere moving through 'keyframes' of (time, center), sorted by time. There must be at least one.
+    pub fn make(keyframes: Vec<(f64, Vector)>, radius: f64, material: Material) -> Self {
+        let sphere = Sphere::make(&Vector::make(0., 0., 0.), radius, material);
+        MovingSphere {
+            motion: Moving::make(keyframes, Box::new(sphere)),
+        }
+    }
+
+    /// Makes a sphere that moves in a straight line from 'start' at time 0.0 to 'end' at time 1.0.
+    pub fn linear(start: Vector, end: Vector, radius: f64, material: Material) -> Self {
+        MovingSphere::make(vec![(0., start), (1., end)], radius, material)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.motion.hit(ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        self.motion.intervals(ray)
+    }
+}
+
+/// Any object modeled around the origin that moves through the given keyframes, linearly in between them.
+pub struct Moving {
+    keyframes: Vec<(f64, Vector)>, // (time, position of the object's origin), sorted by time
+    pub object: Box<dyn Hittable>,
+}
+
+impl Moving {
+    /// Makes 'object' move through 'keyframes' of (time, position), sorted by time. There must be at least one.
+    pub fn make(keyframes: Vec<(f64, Vector)>, object: Box<dyn Hittable>) -> Self {
+        assert!(
+            !keyframes.is_empty(),
+            "A moving object needs at least one keyframe"
+        );
+        Moving { keyframes, object }
+    }
+
+    /// Returns the position of the object's origin at the given time, holding still before the first
+    /// and after the last keyframe.
+    pub fn position(&self, time: f64) -> Vector {
+        let (first_time, first_position) = self.keyframes[0];
+        if time <= first_time {
+            return first_position;
+        }
+
+        for window in self.keyframes.windows(2) {
+            let ((t0, p0), (t1, p1)) = (window[0], window[1]);
+            if time <= t1 {
+                let t = if t1 > t0 { (time - t0) / (t1 - t0) } else { 1. };
+                return p0 + (p1 - p0) * t;
             }
         }
+        self.keyframes[self.keyframes.len() - 1].1
     }
 
-    fn normal(&self, point: Vector) -> Vector {
-        (point - self.center) / self.radius
+    /// Returns 'ray' moved the other way than the object at the ray's time, and how far the object moved.
+    fn local_ray(&self, ray: &Ray) -> (Ray, Vector) {
+        // Moving the ray the other way is the same as moving the object
+        let offset = self.position(ray.time);
+        let local_ray = Ray {
+            origin: ray.origin - offset,
+            direction: ray.direction,
+            media: Vec::new(),
+            wavelength: ray.wavelength,
+            time: ray.time,
+            cone: ray.cone,
+        };
+        (local_ray, offset)
     }
+}
 
-    fn material(&self) -> &Material {
-        &self.material
+impl Hittable for Moving {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (local_ray, offset) = self.local_ray(ray);
+        let mut hit = self.object.hit(&local_ray, t_min, t_max)?;
+        hit.point = hit.point + offset;
+        Some(hit)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let (local_ray, offset) = self.local_ray(ray);
+        let mut intervals = self.object.intervals(&local_ray);
+        for interval in &mut intervals {
+            interval.enter.point = interval.enter.point + offset;
+            interval.exit.point = interval.exit.point + offset;
+        }
+        intervals
+    }
+}
+
+/// A shared object placed in the scene by a transform, so the same shape can appear many times,
+/// turned, or stretched differently along each axis.
+pub struct Instance {
+    pub object: Arc<dyn Hittable>,
+    transform: Matrix4, // from the object's space to the scene
+    inverse: Matrix4,
+}
+
+impl Instance {
+    /// Places 'object' with 'transform', which must not flatten it.
+    pub fn make(object: Arc<dyn Hittable>, transform: Matrix4) -> Self {
+        let inverse = transform
+            .inverse()
+            .expect("Instance transforms can be inverted");
+        Instance {
+            object,
+            transform,
+            inverse,
+        }
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        hit_transformed(
+            &self.transform,
+            &self.inverse,
+            ray,
+            t_min,
+            t_max,
+            |local_ray, t_min, t_max| self.object.hit(local_ray, t_min, t_max),
+        )
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let (local_ray, scale) = transform_ray(&self.inverse, ray);
+        let to_scene = |hit| hit_to_scene(hit, &self.transform, &self.inverse, scale);
+        self.object
+            .intervals(&local_ray)
+            .into_iter()
+            .map(|interval| Interval {
+                enter: to_scene(interval.enter),
+                exit: to_scene(interval.exit),
+            })
+            .collect()
+    }
+}
+
+/// Returns the hit found by 'hit_local' for 'ray' moved into the space that 'transform' takes to the scene,
+/// moved back into the scene. 'inverse' is the inverse of 'transform'.
+pub fn hit_transformed<'a>(
+    transform: &Matrix4,
+    inverse: &Matrix4,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+    hit_local: impl FnOnce(&Ray, f64, f64) -> Option<HitRecord<'a>>,
+) -> Option<HitRecord<'a>> {
+    let (local_ray, scale) = transform_ray(inverse, ray);
+    let local = hit_local(&local_ray, t_min * scale, t_max * scale)?;
+    Some(hit_to_scene(local, transform, inverse, scale))
+}
+
+/// Returns 'ray' moved by 'inverse' into an object's space, and how many times longer distances along the ray
+/// are there than in the scene.
+pub fn transform_ray(inverse: &Matrix4, ray: &Ray) -> (Ray, f64) {
+    let direction = inverse.transform_vector(ray.direction);
+    let scale = direction.length();
+    let local_ray = Ray {
+        origin: inverse.transform_point(ray.origin),
+        direction: direction / scale,
+        media: Vec::new(),
+        wavelength: ray.wavelength,
+        time: ray.time,
+        cone: (ray.cone.0 * scale, ray.cone.1),
+    };
+    (local_ray, scale)
+}
+
+/// Moves a hit of a ray made by 'transform_ray' back into the scene with 'transform'.
+pub fn hit_to_scene<'a>(
+    local: HitRecord<'a>,
+    transform: &Matrix4,
+    inverse: &Matrix4,
+    scale: f64,
+) -> HitRecord<'a> {
+    // Normals stay perpendicular to the surface by using the inverse transpose
+    let normal_matrix = inverse.transpose();
+    let to_scene = |normal: Vector| normal_matrix.transform_vector(normal).normalized();
+    let normal = to_scene(local.normal);
+    let tangent = transform.transform_vector(local.tangent);
+    let tangent = tangent - normal * tangent.dot(&normal);
+    HitRecord {
+        t: local.t / scale,
+        point: transform.transform_point(local.point),
+        geometric_normal: to_scene(local.geometric_normal),
+        normal,
+        tangent: if tangent.length() > 1e-12 {
+            tangent.normalized()
+        } else {
+            normal.orthonormal_basis().0
+        },
+        ..local
     }
 }
 
 pub struct Triangle {
     pub vertices: (Vector, Vector, Vector),
     pub normal: Vector,
+    pub uvs: [(f64, f64); 3], // texture coordinates of the vertices
     pub material: Material,
 }
 
@@ -139,43 +887,200 @@ impl Triangle {
         Triangle {
             vertices: (a, b, c),
             normal: n,
+            uvs: [(0., 0.), (1., 0.), (0., 1.)],
             material: material,
         }
     }
 }
 impl Hittable for Triangle {
-    fn hit(&self, ray: &Ray) -> Option<f64> {
-        // Algorithm from: https://www.lighthouse3d.com/tutorials/maths/ray-triangle-intersection/
-
-        let (v0, v1, v2) = self.vertices;
-        let (e1, e2) = (v1 - v0, v2 - v0);
-        let h = ray.direction.cross(&e2);
-        let a = e1.dot(&h);
-        if -0.00001 < a && a < 0.00001 {
-            return None;
-        }
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (t, u, v) = hit_triangle(self.vertices, ray, t_min, t_max)?;
+        let uv = interpolate_uv(self.uvs, u, v);
+        let uv_size = uv_size(self.vertices, self.uvs);
+        let tangent = triangle_tangent(self.vertices, self.uvs, self.normal);
+        Some(HitRecord::new(
+            ray,
+            t,
+            self.normal,
+            tangent,
+            uv,
+            uv_size,
+            &self.material,
+        ))
+    }
+}
 
-        let f = 1.0 / a;
-        let s = ray.origin - v0;
-        let u = f * s.dot(&h);
-        if u > 1. || u < 0. {
-            return None;
-        }
+/// Returns the distance along 'ray' to the triangle with the given vertices and the barycentric
+/// coordinates of the intersection, if it lies between 't_min' and 't_max'.
+pub fn hit_triangle(
+    vertices: (Vector, Vector, Vector),
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    // Algorithm from: https://www.lighthouse3d.com/tutorials/maths/ray-triangle-intersection/
 
-        let q = s.cross(&e1);
-        let v = f * ray.direction.dot(&q);
-        if v < 0. || u + v > 1. {
-            return None;
+    let (v0, v1, v2) = vertices;
+    let (e1, e2) = (v1 - v0, v2 - v0);
+    let h = ray.direction.cross(&e2);
+    let a = e1.dot(&h);
+    if -0.00001 < a && a < 0.00001 {
+        return None;
+    }
+
+    let f = 1.0 / a;
+    let s = ray.origin - v0;
+    let u = f * s.dot(&h);
+    if u > 1. || u < 0. {
+        return None;
+    }
+
+    let q = s.cross(&e1);
+    let v = f * ray.direction.dot(&q);
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let t = f * e2.dot(&q);
+    if t_min < t && t < t_max {
+        Some((t, u, v))
+    } else {
+        None
+    }
+}
+
+/// Interpolates the texture coordinates of a triangle's vertices at the barycentric coordinates 'u' and 'v'.
+fn interpolate_uv(uvs: [(f64, f64); 3], u: f64, v: f64) -> (f64, f64) {
+    let w = 1. - u - v;
+    (
+        uvs[0].0 * w + uvs[1].0 * u + uvs[2].0 * v,
+        uvs[0].1 * w + uvs[1].1 * u + uvs[2].1 * v,
+    )
+}
+
+/// Returns how large one unit of UV is on a triangle, comparing its area to the area of its UVs.
+fn uv_size(vertices: (Vector, Vector, Vector), uvs: [(f64, f64); 3]) -> f64 {
+    let (v0, v1, v2) = vertices;
+    let area = (v1 - v0).cross(&(v2 - v0)).length();
+    let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+    let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+    let uv_area = (du1 * dv2 - du2 * dv1).abs();
+    if uv_area > 0. {
+        (area / uv_area).sqrt()
+    } else {
+        1.
+    }
+}
+
+/// Returns the direction in which u grows along a triangle with the given vertices, UVs and normal.
+fn triangle_tangent(
+    vertices: (Vector, Vector, Vector),
+    uvs: [(f64, f64); 3],
+    normal: Vector,
+) -> Vector {
+    let (v0, v1, v2) = vertices;
+    let (e1, e2) = (v1 - v0, v2 - v0);
+    let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+    let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+    let determinant = du1 * dv2 - du2 * dv1;
+    if determinant.abs() < 1e-12 {
+        return normal.orthonormal_basis().0;
+    }
+
+    // Only the part of the tangent that lies in the triangle's plane matters
+    let tangent = (e1 * dv2 - e2 * dv1) / determinant;
+    let tangent = tangent - normal * tangent.dot(&normal);
+    if tangent.length() > 1e-12 {
+        tangent.normalized()
+    } else {
+        normal.orthonormal_basis().0
+    }
+}
+
+/// Triangles that share vertices, texture coordinates and a single material.
+pub struct Mesh {
+    pub vertices: Vec<Vector>,
+    pub uvs: Vec<(f64, f64)>, // one per vertex, or empty to use the barycentric coordinates of each face
+    pub faces: Vec<[usize; 3]>, // vertex indices, counter-clockwise when seen from outside
+    pub material: Material,
+}
+
+impl Mesh {
+    pub fn make(
+        vertices: Vec<Vector>,
+        uvs: Vec<(f64, f64)>,
+        faces: Vec<[usize; 3]>,
+        material: Material,
+    ) -> Self {
+        assert!(uvs.is_empty() || uvs.len() == vertices.len());
+        Mesh {
+            vertices,
+            uvs,
+            faces,
+            material,
         }
+    }
+}
 
-        let t = f * e2.dot(&q);
-        if t > 0.001 { Some(t) } else { None }
+impl Mesh {
+    /// Makes the hit at distance 't' along 'ray' on the given face, at barycentric coordinates 'u' and 'v'.
+    fn face_record(&self, ray: &Ray, t: f64, u: f64, v: f64, face: [usize; 3]) -> HitRecord<'_> {
+        let [a, b, c] = face;
+        let (v0, v1, v2) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+        let normal = (v1 - v0).cross(&(v2 - v0)).normalized();
+        let uvs = if self.uvs.is_empty() {
+            [(0., 0.), (1., 0.), (0., 1.)]
+        } else {
+            [self.uvs[a], self.uvs[b], self.uvs[c]]
+        };
+        let uv = interpolate_uv(uvs, u, v);
+        let uv_size = uv_size((v0, v1, v2), uvs);
+        let tangent = triangle_tangent((v0, v1, v2), uvs, normal);
+        HitRecord::new(ray, t, normal, tangent, uv, uv_size, &self.material)
     }
-    fn normal(&self, _: Vector) -> Vector {
-        self.normal
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut closest = None;
+        let mut t_max = t_max;
+        for &[a, b, c] in &self.faces {
+            let vertices = (self.vertices[a], self.vertices[b], self.vertices[c]);
+            if let Some((t, u, v)) = hit_triangle(vertices, ray, t_min, t_max) {
+                t_max = t;
+                closest = Some((t, u, v, [a, b, c]));
+            }
+        }
+
+        let (t, u, v, face) = closest?;
+        Some(self.face_record(ray, t, u, v, face))
     }
-    fn material(&self) -> &Material {
-        &self.material
+
+    /// Treats the mesh as a closed solid, entering it through faces that point towards the ray
+    /// and exiting it through faces that point away.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let mut hits: Vec<HitRecord> = self
+            .faces
+            .iter()
+            .filter_map(|&[a, b, c]| {
+                let vertices = (self.vertices[a], self.vertices[b], self.vertices[c]);
+                let (t, u, v) = hit_triangle(vertices, ray, f64::NEG_INFINITY, f64::INFINITY)?;
+                Some(self.face_record(ray, t, u, v, [a, b, c]))
+            })
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        // Rays through an edge hit both faces next to it, so only changes between inside and outside count
+        let mut intervals = Vec::new();
+        let mut enter = None;
+        for hit in hits {
+            match (enter.take(), hit.front_face) {
+                (None, true) => enter = Some(hit),
+                (Some(enter), false) => intervals.push(Interval { enter, exit: hit }),
+                (current, _) => enter = current,
+            }
+        }
+        intervals
     }
 }
 
@@ -183,27 +1088,232 @@ pub struct Plane {
     pub point: Vector,
     pub normal: Vector,
     pub material: Material,
+    pub uv_axis: Option<Vector>, // direction of the texture's u axis within the plane, None picks one
+    pub uv_scale: f64,           // size of one texture tile
 }
 impl Hittable for Plane {
-    fn hit(&self, ray: &Ray) -> Option<f64> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let denom = self.normal.dot(&ray.direction);
         if denom.abs() < 1e-6 {
             return None; // Ray is parallel to the plane
         }
 
         let t = (self.point - ray.origin).dot(&self.normal) / denom;
-        if t > 0.0 {
-            Some(ray.origin + ray.direction * t)
-        } else {
-            None // Plane is behind the camera
+        if t <= t_min || t >= t_max {
+            return None; // Plane is behind the camera or further than 't_max'
         }
-        .map(|p| (p - ray.origin).length())
+
+        // The texture is tiled over the plane, starting at 'point'
+        let point = ray.origin + ray.direction * t;
+        let tangent = match self.uv_axis {
+            Some(axis) => (axis - self.normal * axis.dot(&self.normal)).normalized(),
+            None => self.normal.orthonormal_basis().0,
+        };
+        let bitangent = self.normal.cross(&tangent);
+        let offset = (point - self.point) / self.uv_scale;
+        let uv = (offset.dot(&tangent), offset.dot(&bitangent));
+        Some(HitRecord::new(
+            ray,
+            t,
+            self.normal,
+            tangent,
+            uv,
+            self.uv_scale,
+            &self.material,
+        ))
     }
+}
 
-    fn normal(&self, _: Vector) -> Vector {
-        self.normal
+/// A flat parallelogram facing both ways, spanned from 'corner' by the edges 'u' and 'v'.
+/// A texture is stretched over it once, with its u axis along 'u'.
+pub struct Quad {
+    pub corner: Vector,
+    pub u: Vector,
+    pub v: Vector,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn make(corner: Vector, u: Vector, v: Vector, material: Material) -> Self {
+        Quad {
+            corner,
+            u,
+            v,
+            material,
+        }
     }
-    fn material(&self) -> &Material {
-        &self.material
+
+    /// Returns a light shining from the whole quad, which may be any parallelogram.
+    /// The light itself can't be seen, and the quad is only used for its shape, so it needn't be in the scene.
+    pub fn light(&self, intensity: f64) -> Light {
+        Light {
+            position: self.corner,
+            intensity,
+            area: Some((self.u, self.v)),
+        }
     }
 }
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let n = self.u.cross(&self.v);
+        let denom = n.dot(&ray.direction);
+        if denom.abs() < 1e-12 {
+            return None; // Ray is parallel to the quad
+        }
+
+        let t = (self.corner - ray.origin).dot(&n) / denom;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        // Coordinates of the point along the edges, both between 0.0 and 1.0 within the quad
+        let offset = ray.origin + ray.direction * t - self.corner;
+        let w = n / n.dot(&n);
+        let (a, b) = (w.dot(&offset.cross(&self.v)), w.dot(&self.u.cross(&offset)));
+        if !(0. ..=1.).contains(&a) || !(0. ..=1.).contains(&b) {
+            return None;
+        }
+
+        // Both sides are front sides, so either can be lit and seen
+        let normal = if denom < 0. { n } else { -n };
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal.normalized(),
+            self.u.normalized(),
+            (a, b),
+            self.u.length().max(self.v.length()),
+            &self.material,
+        ))
+    }
+}
+
+/// A flat round disk facing both ways, with u growing from the center to the edge and v around it.
+pub struct Disk {
+    pub center: Vector,
+    pub normal: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn make(center: Vector, normal: Vector, radius: f64, material: Material) -> Self {
+        Disk {
+            center,
+            normal: normal.normalized(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let radii = (0., self.radius);
+        hit_annulus(
+            self.center,
+            self.normal,
+            radii,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+}
+
+/// A flat ring between two circles, like a disk with a hole in the middle.
+/// u grows from the inner to the outer edge, so a texture one pixel high gives the ring's profile.
+pub struct Annulus {
+    pub center: Vector,
+    pub normal: Vector,
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    pub material: Material,
+}
+
+impl Annulus {
+    pub fn make(
+        center: Vector,
+        normal: Vector,
+        inner_radius: f64,
+        outer_radius: f64,
+        material: Material,
+    ) -> Self {
+        Annulus {
+            center,
+            normal: normal.normalized(),
+            inner_radius,
+            outer_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Annulus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let radii = (self.inner_radius, self.outer_radius);
+        hit_annulus(
+            self.center,
+            self.normal,
+            radii,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+}
+
+/// Returns the intersection of 'ray' with the flat ring around 'center' between the (inner, outer) 'radii',
+/// if it lies between 't_min' and 't_max'.
+fn hit_annulus<'a>(
+    center: Vector,
+    normal: Vector,
+    radii: (f64, f64),
+    material: &'a Material,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'a>> {
+    let denom = normal.dot(&ray.direction);
+    if denom.abs() < 1e-6 {
+        return None;
+    }
+
+    let t = (center - ray.origin).dot(&normal) / denom;
+    if t <= t_min || t >= t_max {
+        return None;
+    }
+
+    let (inner, outer) = radii;
+    let offset = ray.origin + ray.direction * t - center;
+    let distance = offset.length();
+    if distance < inner || distance > outer {
+        return None;
+    }
+
+    // u runs outwards, v counter-clockwise around the normal starting from an arbitrary direction
+    let (x_axis, y_axis) = normal.orthonormal_basis();
+    let angle = offset.dot(&y_axis).atan2(offset.dot(&x_axis));
+    let uv = (
+        (distance - inner) / (outer - inner),
+        angle.rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU,
+    );
+    let tangent = if distance > 1e-9 {
+        offset / distance
+    } else {
+        x_axis
+    };
+    // Both sides are front sides, so either can be lit and seen
+    Some(HitRecord::new(
+        ray,
+        t,
+        if denom < 0. { normal } else { -normal },
+        tangent,
+        uv,
+        outer - inner,
+        material,
+    ))
+}
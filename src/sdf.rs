@@ -0,0 +1,197 @@
+use crate::camera::Ray;
+use crate::scene::{HitRecord, Hittable, Material, sphere_roots};
+use crate::shapes::around_axis_tangent;
+use crate::vectors::Vector;
+use std::f64::consts::{PI, TAU};
+
+const MAX_STEPS: u32 = 256;
+const SURFACE_DISTANCE: f64 = 1e-4; // how close a step has to get to count as a hit, at the least
+
+/// An object whose surface is where the distance function of 'shape' is zero, found by sphere tracing:
+/// each step moves the ray forward by the distance to the nearest surface, so it never passes through one.
+/// The shape is modeled around the origin and must fit in the bounding sphere of 'radius' around 'center'.
+pub struct Sdf {
+    pub shape: Shape,
+    pub center: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+/// A solid described by how far any point is from its surface, negative inside of it.
+pub enum Shape {
+    Sphere(f64),                              // radius
+    Cuboid(Vector),                           // half of the size along each axis
+    Torus(f64, f64),                          // major and minor radius, around the y axis
+    Capsule(f64, f64),                        // half of the length along the y axis and radius
+    Mandelbulb(f64, u32),                     // power and number of iterations of the fractal
+    Translated(Vector, Box<Shape>),           // moved by the offset
+    SmoothUnion(Box<Shape>, Box<Shape>, f64), // both shapes blended together over this distance
+    Subtraction(Box<Shape>, Box<Shape>),      // the second shape carved out of the first
+    Scaled(f64, Box<Shape>),                  // made larger or smaller by the factor
+    Twist(Box<Shape>, f64),                   // radians turned around the y axis per unit of height
+}
+
+impl Sdf {
+    pub fn make(shape: Shape, center: Vector, radius: f64, material: Material) -> Self {
+        Sdf {
+            shape,
+            center,
+            radius,
+            material,
+        }
+    }
+
+    /// Returns the normal at 'p' from how the distance changes 'step' away from it along each axis.
+    fn normal(&self, p: Vector, step: f64) -> Vector {
+        let axes = [
+            Vector::make(step, 0., 0.),
+            Vector::make(0., step, 0.),
+            Vector::make(0., 0., step),
+        ];
+        let [x, y, z] = axes.map(|e| self.shape.distance(p + e) - self.shape.distance(p - e));
+        let gradient = Vector::make(x, y, z);
+        if gradient.length() > 0. {
+            gradient.normalized()
+        } else {
+            Vector::make(0., 1., 0.)
+        }
+    }
+}
+
+impl Hittable for Sdf {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // Only the part of the ray within the bounding sphere is marched
+        let (enter, exit) = sphere_roots(self.center, self.radius, ray)?;
+        let scale = ray.direction.length();
+        let mut t = enter.max(t_min);
+        let end = exit.min(t_max);
+
+        let mut steps = 0;
+        while t < end && steps < MAX_STEPS {
+            let p = ray.origin + ray.direction * t - self.center;
+            // Rays starting inside the shape, like refracted ones, march towards the surface all the same
+            let distance = self.shape.distance(p).abs();
+            // Details smaller than a pixel can't be seen, and only make the surface and its normals noisy
+            let precision = SURFACE_DISTANCE.max(ray.footprint(t * scale) / 2.);
+            if distance < precision {
+                if t <= t_min {
+                    // Step off the surface the ray starts on
+                    t += precision * 2. / scale;
+                    steps += 1;
+                    continue;
+                }
+                let normal = self.normal(p, precision);
+                // The texture is wrapped around the center like on a sphere
+                let u = 0.5 + p.z.atan2(p.x) / TAU;
+                let v = 0.5 + (p.y / p.length().max(1e-12)).clamp(-1., 1.).asin() / PI;
+                // u grows around the y axis, which is tilted to lie along the surface
+                let around = around_axis_tangent(p);
+                let along = around - normal * normal.dot(&around);
+                let tangent = if along.length() > 1e-12 {
+                    along.normalized()
+                } else {
+                    normal.orthonormal_basis().0
+                };
+                return Some(HitRecord::new(
+                    ray,
+                    t,
+                    normal,
+                    tangent,
+                    (u, v),
+                    self.radius * TAU,
+                    &self.material,
+                ));
+            }
+            t += distance / scale;
+            steps += 1;
+        }
+        None
+    }
+}
+
+impl Shape {
+    /// Returns the distance from 'p' to the surface of the shape, negative inside of it.
+    /// The distance may be underestimated, but never overestimated, so marching by it is safe.
+    pub fn distance(&self, p: Vector) -> f64 {
+        match self {
+            Shape::Sphere(radius) => p.length() - radius,
+            Shape::Cuboid(half) => {
+                let q = Vector::make(p.x.abs() - half.x, p.y.abs() - half.y, p.z.abs() - half.z);
+                let outside = Vector::make(q.x.max(0.), q.y.max(0.), q.z.max(0.)).length();
+                let inside = q.x.max(q.y).max(q.z).min(0.);
+                outside + inside
+            }
+            Shape::Torus(major, minor) => {
+                let ring = p.x.hypot(p.z) - major;
+                ring.hypot(p.y) - minor
+            }
+            Shape::Capsule(half_length, radius) => {
+                let y = p.y.clamp(-half_length, *half_length);
+                (p - Vector::make(0., y, 0.)).length() - radius
+            }
+            Shape::Mandelbulb(power, iterations) => mandelbulb(p, *power, *iterations),
+            Shape::Translated(offset, shape) => shape.distance(p - *offset),
+            Shape::Scaled(factor, shape) => shape.distance(p / *factor) * factor,
+            Shape::SmoothUnion(a, b, k) => {
+                let (a, b) = (a.distance(p), b.distance(p));
+                if *k <= 0. {
+                    return a.min(b);
+                }
+                let h = (k - (a - b).abs()).max(0.) / k;
+                a.min(b) - h * h * k / 4.
+            }
+            Shape::Subtraction(a, b) => a.distance(p).max(-b.distance(p)),
+            Shape::Twist(shape, rate) => {
+                let (sin, cos) = (rate * p.y).sin_cos();
+                let q = Vector::make(cos * p.x - sin * p.z, p.y, sin * p.x + cos * p.z);
+                // Twisting stretches space the more the further it is from the axis
+                shape.distance(q) / (1. + rate.abs() * p.x.hypot(p.z))
+            }
+        }
+    }
+
+    pub fn translated(self, offset: Vector) -> Shape {
+        Shape::Translated(offset, Box::new(self))
+    }
+
+    pub fn scaled(self, factor: f64) -> Shape {
+        Shape::Scaled(factor, Box::new(self))
+    }
+
+    pub fn smooth_union(self, other: Shape, smoothness: f64) -> Shape {
+        Shape::SmoothUnion(Box::new(self), Box::new(other), smoothness)
+    }
+
+    pub fn subtract(self, other: Shape) -> Shape {
+        Shape::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn twist(self, rate: f64) -> Shape {
+        Shape::Twist(Box::new(self), rate)
+    }
+}
+
+/// Returns the estimated distance to a Mandelbulb, the set of points that stay bounded when
+/// repeatedly raised to 'power' in spherical coordinates, with the y axis as its pole.
+fn mandelbulb(p: Vector, power: f64, iterations: u32) -> f64 {
+    let mut z = p;
+    let mut derivative = 1.;
+    let mut r = z.length();
+    for _ in 0..iterations {
+        if r > 2. {
+            break;
+        }
+        let theta = (z.y / r.max(1e-12)).clamp(-1., 1.).acos() * power;
+        let phi = z.z.atan2(z.x) * power;
+        derivative = r.powf(power - 1.) * power * derivative + 1.;
+        let scaled = r.powf(power);
+        z = Vector::make(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            theta.sin() * phi.sin(),
+        ) * scaled
+            + p;
+        r = z.length();
+    }
+    0.5 * r.ln() * r / derivative
+}
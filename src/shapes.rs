@@ -0,0 +1,772 @@
+use crate::camera::Ray;
+use crate::scene::{HitRecord, Hittable, Instance, Interval, Material};
+use crate::vectors::{Matrix4, Vector};
+use std::f64::consts::{PI, TAU};
+use std::sync::Arc;
+
+/// A box between the corners 'min' and 'max', with its faces along the axes.
+/// Every face shows a whole copy of a texture.
+pub struct Cuboid {
+    pub min: Vector,
+    pub max: Vector,
+    pub material: Material,
+}
+
+impl Cuboid {
+    pub fn make(min: Vector, max: Vector, material: Material) -> Self {
+        Cuboid { min, max, material }
+    }
+
+    /// Returns a box with the given center and size along its own axes, turned by 'angle' radians around 'axis'.
+    pub fn oriented(
+        center: Vector,
+        size: Vector,
+        axis: Vector,
+        angle: f64,
+        material: Material,
+    ) -> Instance {
+        let cuboid = Cuboid::make(-size / 2., size / 2., material);
+        let transform = Matrix4::translation(center) * Matrix4::rotation(axis, angle);
+        Instance::make(Arc::new(cuboid), transform)
+    }
+}
+
+impl Solid for Cuboid {
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing> {
+        let axes = [
+            Vector::make(1., 0., 0.),
+            Vector::make(0., 1., 0.),
+            Vector::make(0., 0., 1.),
+        ];
+        let mut enter = (f64::NEG_INFINITY, Vector::make(0., 0., 0.));
+        let mut exit = (f64::INFINITY, Vector::make(0., 0., 0.));
+        for axis in axes {
+            let (o, d) = (origin.dot(&axis), direction.dot(&axis));
+            let (low, high) = (self.min.dot(&axis), self.max.dot(&axis));
+            if d.abs() < 1e-12 {
+                if o < low || o > high {
+                    return Vec::new();
+                }
+                continue;
+            }
+
+            // The ray meets the face it faces first
+            let mut near = ((low - o) / d, -axis);
+            let mut far = ((high - o) / d, axis);
+            if near.0 > far.0 {
+                std::mem::swap(&mut near, &mut far);
+            }
+            if near.0 > enter.0 {
+                enter = near;
+            }
+            if far.0 < exit.0 {
+                exit = far;
+            }
+        }
+        if enter.0 > exit.0 {
+            Vec::new()
+        } else {
+            vec![enter, exit]
+        }
+    }
+
+    fn surface(&self, point: Vector, normal: Vector) -> Surface {
+        let size = self.max - self.min;
+        let offset = point - self.min;
+        // The other two axes of the face the point lies on, u along the first one
+        let (u_axis, v_axis) = if normal.x.abs() > 0.5 {
+            (Vector::make(0., 0., 1.), Vector::make(0., 1., 0.))
+        } else if normal.y.abs() > 0.5 {
+            (Vector::make(1., 0., 0.), Vector::make(0., 0., 1.))
+        } else {
+            (Vector::make(1., 0., 0.), Vector::make(0., 1., 0.))
+        };
+        let (u_size, v_size) = (size.dot(&u_axis), size.dot(&v_axis));
+        Surface {
+            uv: (offset.dot(&u_axis) / u_size, offset.dot(&v_axis) / v_size),
+            tangent: u_axis,
+            uv_size: u_size.max(v_size),
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        hit_solid(self, &Frame::world(), &self.material, ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        solid_intervals(self, &Frame::world(), &self.material, ray)
+    }
+}
+
+/// A round cylinder from the center of its 'base' to the center of its 'top', closed by flat caps.
+pub struct Cylinder {
+    pub base: Vector,
+    pub top: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Cylinder {
+    pub fn make(base: Vector, top: Vector, radius: f64, material: Material) -> Self {
+        Cylinder {
+            base,
+            top,
+            radius,
+            material,
+        }
+    }
+
+    fn height(&self) -> f64 {
+        (self.top - self.base).length()
+    }
+}
+
+impl Solid for Cylinder {
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing> {
+        let height = self.height();
+        let mut crossings: Vec<Crossing> = tube_roots(origin, direction, self.radius)
+            .into_iter()
+            .map(|t| (t, origin + direction * t))
+            .filter(|(_, p)| 0. <= p.y && p.y <= height)
+            .map(|(t, p)| (t, Vector::make(p.x, 0., p.z) / self.radius))
+            .collect();
+        crossings.extend(cap_crossing(origin, direction, 0., self.radius, false));
+        crossings.extend(cap_crossing(origin, direction, height, self.radius, true));
+        convex_span(crossings)
+    }
+
+    fn surface(&self, point: Vector, normal: Vector) -> Surface {
+        if normal.y.abs() > 0.5 {
+            return cap_surface(point, self.radius);
+        }
+        Surface {
+            uv: (around_axis(point), point.y / self.height()),
+            tangent: around_axis_tangent(point),
+            uv_size: TAU * self.radius,
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let frame = Frame::along(self.base, self.top - self.base);
+        hit_solid(self, &frame, &self.material, ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let frame = Frame::along(self.base, self.top - self.base);
+        solid_intervals(self, &frame, &self.material, ray)
+    }
+}
+
+/// A round cone from a flat base of the given 'radius' around 'base' to a point at 'apex'.
+pub struct Cone {
+    pub base: Vector,
+    pub apex: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Cone {
+    pub fn make(base: Vector, apex: Vector, radius: f64, material: Material) -> Self {
+        Cone {
+            base,
+            apex,
+            radius,
+            material,
+        }
+    }
+
+    fn height(&self) -> f64 {
+        (self.apex - self.base).length()
+    }
+}
+
+impl Solid for Cone {
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing> {
+        // The side is the part of x² + z² = (k (height - y))² between the base and the apex
+        let height = self.height();
+        let k2 = (self.radius / height).powi(2);
+        let (o, d) = (origin, direction);
+        let h = height - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2. * (o.x * d.x + o.z * d.z + k2 * h * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * h * h;
+
+        let mut crossings: Vec<Crossing> = solve_quadratic(a, b, c)
+            .into_iter()
+            .map(|t| (t, o + d * t))
+            .filter(|(_, p)| 0. <= p.y && p.y <= height)
+            .map(|(t, p)| {
+                let gradient = Vector::make(p.x, k2 * (height - p.y), p.z);
+                let normal = if gradient.length() > 1e-12 {
+                    gradient.normalized()
+                } else {
+                    Vector::make(0., 1., 0.)
+                };
+                (t, normal)
+            })
+            .collect();
+        crossings.extend(cap_crossing(origin, direction, 0., self.radius, false));
+        convex_span(crossings)
+    }
+
+    fn surface(&self, point: Vector, normal: Vector) -> Surface {
+        if normal.y < -0.5 && point.y.abs() < 1e-6 {
+            return cap_surface(point, self.radius);
+        }
+        Surface {
+            uv: (around_axis(point), point.y / self.height()),
+            tangent: around_axis_tangent(point),
+            uv_size: TAU * self.radius,
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let frame = Frame::along(self.base, self.apex - self.base);
+        hit_solid(self, &frame, &self.material, ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let frame = Frame::along(self.base, self.apex - self.base);
+        solid_intervals(self, &frame, &self.material, ray)
+    }
+}
+
+/// A ring shaped tube around 'center', whose middle is a circle of 'major_radius' around 'axis'.
+pub struct Torus {
+    pub center: Vector,
+    pub axis: Vector,
+    pub major_radius: f64, // from the center to the middle of the tube
+    pub minor_radius: f64, // of the tube
+    pub material: Material,
+}
+
+impl Torus {
+    pub fn make(
+        center: Vector,
+        axis: Vector,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Material,
+    ) -> Self {
+        Torus {
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Solid for Torus {
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing> {
+        let (big, small) = (self.major_radius, self.minor_radius);
+        // Starting from the point of the line closest to the center keeps the coefficients small
+        let shift = -origin.dot(&direction);
+        let o = origin + direction * shift;
+        let d = direction;
+
+        // (|p|² + R² - r²)² = 4R² (x² + z²), with p = o + d t and |d| = 1
+        let b = 2. * o.dot(&d);
+        let c = o.dot(&o) + big * big - small * small;
+        let e = d.x * d.x + d.z * d.z;
+        let f = 2. * (o.x * d.x + o.z * d.z);
+        let g = o.x * o.x + o.z * o.z;
+        let four_r2 = 4. * big * big;
+        let mut roots = solve_quartic([
+            1.,
+            2. * b,
+            b * b + 2. * c - four_r2 * e,
+            2. * b * c - four_r2 * f,
+            c * c - four_r2 * g,
+        ]);
+        roots.sort_by(|a, b| a.total_cmp(b));
+
+        // The line enters and exits the tube in turns, and grazing it gives a pair of equal roots
+        let crossings: Vec<Crossing> = roots
+            .into_iter()
+            .map(|t| {
+                let p = o + d * t;
+                let ring = Vector::make(p.x, 0., p.z);
+                let ring = if ring.length() > 1e-12 {
+                    ring.normalized() * big
+                } else {
+                    Vector::make(big, 0., 0.)
+                };
+                (t + shift, (p - ring).normalized())
+            })
+            .collect();
+        let pairs = crossings.len() / 2 * 2;
+        crossings[..pairs].to_vec()
+    }
+
+    fn surface(&self, point: Vector, normal: Vector) -> Surface {
+        // v goes around the tube, starting on its outer edge
+        let outward = Vector::make(point.x, 0., point.z);
+        let outward = if outward.length() > 1e-12 {
+            outward.normalized()
+        } else {
+            Vector::make(1., 0., 0.)
+        };
+        let angle = normal.y.atan2(normal.dot(&outward));
+        Surface {
+            uv: (around_axis(point), angle.rem_euclid(TAU) / TAU),
+            tangent: around_axis_tangent(point),
+            uv_size: TAU * self.major_radius,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let frame = Frame::along(self.center, self.axis);
+        hit_solid(self, &frame, &self.material, ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let frame = Frame::along(self.center, self.axis);
+        solid_intervals(self, &frame, &self.material, ray)
+    }
+}
+
+/// A cylinder from 'start' to 'end' with a half sphere on either end, like a pill.
+pub struct Capsule {
+    pub start: Vector,
+    pub end: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Capsule {
+    pub fn make(start: Vector, end: Vector, radius: f64, material: Material) -> Self {
+        Capsule {
+            start,
+            end,
+            radius,
+            material,
+        }
+    }
+
+    fn length(&self) -> f64 {
+        (self.end - self.start).length()
+    }
+}
+
+impl Solid for Capsule {
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing> {
+        let length = self.length();
+        let r = self.radius;
+        let mut crossings: Vec<Crossing> = tube_roots(origin, direction, r)
+            .into_iter()
+            .map(|t| (t, origin + direction * t))
+            .filter(|(_, p)| 0. <= p.y && p.y <= length)
+            .map(|(t, p)| (t, Vector::make(p.x, 0., p.z) / r))
+            .collect();
+
+        // Each half sphere only counts beyond its end of the tube
+        for (center_y, below) in [(0., true), (length, false)] {
+            let center = Vector::make(0., center_y, 0.);
+            let oc = origin - center;
+            let b = oc.dot(&direction);
+            let roots = solve_quadratic(1., 2. * b, oc.dot(&oc) - r * r);
+            for t in roots {
+                let p = origin + direction * t;
+                if (below && p.y <= 0.) || (!below && p.y >= length) {
+                    crossings.push((t, (p - center) / r));
+                }
+            }
+        }
+        convex_span(crossings)
+    }
+
+    fn surface(&self, point: Vector, _normal: Vector) -> Surface {
+        let r = self.radius;
+        Surface {
+            uv: (around_axis(point), (point.y + r) / (self.length() + 2. * r)),
+            tangent: around_axis_tangent(point),
+            uv_size: TAU * r,
+        }
+    }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let frame = Frame::along(self.start, self.end - self.start);
+        hit_solid(self, &frame, &self.material, ray, t_min, t_max)
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval<'_>> {
+        let frame = Frame::along(self.start, self.end - self.start);
+        solid_intervals(self, &frame, &self.material, ray)
+    }
+}
+
+/// A distance along a ray where its line crosses the surface of a solid, with the outward normal there.
+type Crossing = (f64, Vector);
+
+/// Texture coordinates at a point of a solid's surface.
+struct Surface {
+    uv: (f64, f64),
+    tangent: Vector, // direction in which u grows
+    uv_size: f64,    // about how large one unit of UV is
+}
+
+/// A shape described in its own frame, where its axis is y.
+trait Solid {
+    /// Returns where the whole line through the local ray enters and exits the shape, in pairs sorted by distance.
+    fn crossings(&self, origin: Vector, direction: Vector) -> Vec<Crossing>;
+
+    /// Returns the texture coordinates at a local 'point' of the surface with the given 'normal'.
+    fn surface(&self, point: Vector, normal: Vector) -> Surface;
+}
+
+/// Position and orientation of a shape in the scene. The axes are unit vectors at right angles to each other.
+struct Frame {
+    origin: Vector,
+    x: Vector,
+    y: Vector,
+    z: Vector,
+}
+
+impl Frame {
+    /// Returns the frame of the scene itself.
+    fn world() -> Frame {
+        Frame {
+            origin: Vector::make(0., 0., 0.),
+            x: Vector::make(1., 0., 0.),
+            y: Vector::make(0., 1., 0.),
+            z: Vector::make(0., 0., 1.),
+        }
+    }
+
+    /// Returns a frame at 'origin' whose y axis points along 'axis'.
+    fn along(origin: Vector, axis: Vector) -> Frame {
+        let (u, v) = axis.orthonormal_basis();
+        Frame {
+            origin,
+            x: u,
+            y: axis.normalized(),
+            z: -v,
+        }
+    }
+
+    fn point_to_local(&self, point: Vector) -> Vector {
+        self.vector_to_local(point - self.origin)
+    }
+
+    fn vector_to_local(&self, vector: Vector) -> Vector {
+        Vector::make(
+            vector.dot(&self.x),
+            vector.dot(&self.y),
+            vector.dot(&self.z),
+        )
+    }
+
+    fn vector_to_world(&self, vector: Vector) -> Vector {
+        self.x * vector.x + self.y * vector.y + self.z * vector.z
+    }
+}
+
+/// Returns the closest hit of 'ray' with 'solid' between 't_min' and 't_max'.
+fn hit_solid<'a>(
+    solid: &impl Solid,
+    frame: &Frame,
+    material: &'a Material,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'a>> {
+    let (origin, direction) = (
+        frame.point_to_local(ray.origin),
+        frame.vector_to_local(ray.direction),
+    );
+    let (t, normal) = solid
+        .crossings(origin, direction)
+        .into_iter()
+        .find(|(t, _)| t_min < *t && *t < t_max)?;
+    Some(solid_record(
+        solid,
+        frame,
+        material,
+        ray,
+        origin + direction * t,
+        (t, normal),
+    ))
+}
+
+/// Returns the insides of 'solid' along the whole line through 'ray'.
+fn solid_intervals<'a>(
+    solid: &impl Solid,
+    frame: &Frame,
+    material: &'a Material,
+    ray: &Ray,
+) -> Vec<Interval<'a>> {
+    let (origin, direction) = (
+        frame.point_to_local(ray.origin),
+        frame.vector_to_local(ray.direction),
+    );
+    let record = |crossing: Crossing| {
+        let point = origin + direction * crossing.0;
+        solid_record(solid, frame, material, ray, point, crossing)
+    };
+    solid
+        .crossings(origin, direction)
+        .chunks(2)
+        .map(|pair| Interval {
+            enter: record(pair[0]),
+            exit: record(pair[1]),
+        })
+        .collect()
+}
+
+/// Makes the hit of 'ray' where it crosses the surface of 'solid' at the local 'point'.
+fn solid_record<'a>(
+    solid: &impl Solid,
+    frame: &Frame,
+    material: &'a Material,
+    ray: &Ray,
+    point: Vector,
+    (t, normal): Crossing,
+) -> HitRecord<'a> {
+    let surface = solid.surface(point, normal);
+    HitRecord::new(
+        ray,
+        t,
+        frame.vector_to_world(normal),
+        frame.vector_to_world(surface.tangent),
+        surface.uv,
+        surface.uv_size,
+        material,
+    )
+}
+
+/// Returns the first and last of the crossings of a convex shape, or none if the line only grazes it.
+fn convex_span(mut crossings: Vec<Crossing>) -> Vec<Crossing> {
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    match (crossings.first(), crossings.last()) {
+        (Some(&first), Some(&last)) if last.0 - first.0 > 1e-9 => vec![first, last],
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the distances at which the line crosses the infinite tube of 'radius' around the y axis.
+fn tube_roots(origin: Vector, direction: Vector, radius: f64) -> Vec<f64> {
+    let a = direction.x * direction.x + direction.z * direction.z;
+    let b = 2. * (origin.x * direction.x + origin.z * direction.z);
+    let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+    solve_quadratic(a, b, c)
+}
+
+/// Returns where the line crosses the flat round cap of 'radius' at height 'y', facing up or down.
+fn cap_crossing(
+    origin: Vector,
+    direction: Vector,
+    y: f64,
+    radius: f64,
+    up: bool,
+) -> Option<Crossing> {
+    if direction.y.abs() < 1e-12 {
+        return None;
+    }
+    let t = (y - origin.y) / direction.y;
+    let p = origin + direction * t;
+    let normal = Vector::make(0., if up { 1. } else { -1. }, 0.);
+    (p.x * p.x + p.z * p.z <= radius * radius).then_some((t, normal))
+}
+
+/// Lays a texture flat over a round cap of 'radius'.
+fn cap_surface(point: Vector, radius: f64) -> Surface {
+    Surface {
+        uv: (0.5 + point.x / (2. * radius), 0.5 + point.z / (2. * radius)),
+        tangent: Vector::make(1., 0., 0.),
+        uv_size: 2. * radius,
+    }
+}
+
+/// Returns how far around the y axis 'point' lies, between 0.0 and 1.0.
+fn around_axis(point: Vector) -> f64 {
+    0.5 + point.z.atan2(point.x) / TAU
+}
+
+/// Returns the direction in which 'around_axis' grows at 'point'.
+pub fn around_axis_tangent(point: Vector) -> Vector {
+    let tangent = Vector::make(-point.z, 0., point.x);
+    if tangent.length() > 1e-12 {
+        tangent.normalized()
+    } else {
+        Vector::make(1., 0., 0.)
+    }
+}
+
+/// Returns the real solutions of a x² + b x + c = 0.
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return if b.abs() < 1e-12 {
+            Vec::new()
+        } else {
+            vec![-c / b]
+        };
+    }
+    let discriminant = b * b - 4. * a * c;
+    // Rounding can push the discriminant of a double root just below zero
+    if discriminant < -1e-12 * b * b {
+        return Vec::new();
+    }
+    // Avoids subtracting nearly equal numbers, which loses precision
+    let q = -0.5 * (b + b.signum() * discriminant.max(0.).sqrt());
+    if q == 0. {
+        return vec![0., 0.];
+    }
+    vec![q / a, c / q]
+}
+
+/// Returns the real solutions of x³ + a x² + b x + c = 0.
+fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substituting x = y - a/3 gives y³ + 3p y + 2q = 0
+    let p = (b - a * a / 3.) / 3.;
+    let q = (2. * a * a * a / 27. - a * b / 3. + c) / 2.;
+    let discriminant = q * q + p * p * p;
+
+    let roots = if discriminant.abs() < 1e-14 {
+        if q.abs() < 1e-14 {
+            vec![0.]
+        } else {
+            let u = (-q).cbrt();
+            vec![2. * u, -u]
+        }
+    } else if discriminant < 0. {
+        // Three real roots, found with trigonometry
+        let phi = (-q / (-p * p * p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.).cos(),
+            -t * (phi - PI / 3.).cos(),
+        ]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+    roots.into_iter().map(|y| y - a / 3.).collect()
+}
+
+/// Returns the real solutions of c[0] x⁴ + c[1] x³ + c[2] x² + c[3] x + c[4] = 0 with Ferrari's method,
+/// refined with a few steps of Newton's method.
+fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    let (a, b, cc, d) = (c[1] / c[0], c[2] / c[0], c[3] / c[0], c[4] / c[0]);
+
+    // Substituting x = y - a/4 gives y⁴ + p y² + q y + r = 0
+    let a2 = a * a;
+    let p = -3. / 8. * a2 + b;
+    let q = a2 * a / 8. - a * b / 2. + cc;
+    let r = -3. / 256. * a2 * a2 + a2 * b / 16. - a * cc / 4. + d;
+
+    let mut roots = if r.abs() < 1e-14 {
+        let mut roots = solve_cubic(0., p, q);
+        roots.push(0.);
+        roots
+    } else {
+        // The largest real root of the resolvent cubic splits the quartic into two quadratics
+        // with real coefficients, since it is never below p/2
+        let z = solve_cubic(-p / 2., -r, r * p / 2. - q * q / 8.)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let u = z * z - r;
+        let v = 2. * z - p;
+        if u < -1e-12 || v < -1e-12 {
+            return Vec::new();
+        }
+        let (u, v) = (u.max(0.).sqrt(), v.max(0.).sqrt());
+        let v = if q < 0. { -v } else { v };
+        let mut roots = solve_quadratic(1., v, z - u);
+        roots.extend(solve_quadratic(1., -v, z + u));
+        roots
+    };
+
+    let polynomial = |x: f64| (((c[0] * x + c[1]) * x + c[2]) * x + c[3]) * x + c[4];
+    let derivative = |x: f64| ((4. * c[0] * x + 3. * c[1]) * x + 2. * c[2]) * x + c[3];
+    for root in &mut roots {
+        *root -= a / 4.;
+        for _ in 0..2 {
+            let slope = derivative(*root);
+            if slope.abs() > 1e-12 {
+                *root -= polynomial(*root) / slope;
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_cubic, solve_quartic};
+
+    /// Checks that 'roots' are the same as 'expected' in any order, allowing repeated roots to appear once or twice.
+    fn assert_roots(roots: &[f64], expected: &[f64], tolerance: f64) {
+        let near = |x: f64, y: f64| (x - y).abs() < tolerance;
+        for root in roots {
+            assert!(
+                expected.iter().any(|&e| near(*root, e)),
+                "unexpected root {root} in {roots:?}, expected {expected:?}"
+            );
+        }
+        for e in expected {
+            assert!(
+                roots.iter().any(|&root| near(root, *e)),
+                "missing root {e} in {roots:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(&solve_cubic(-6., 11., -6.), &[1., 2., 3.], 1e-9);
+        // (x - 2)(x² + 1)
+        assert_roots(&solve_cubic(-2., 1., -2.), &[2.], 1e-9);
+        // (x - 1)²(x + 2)
+        assert_roots(&solve_cubic(0., -3., 2.), &[1., -2.], 1e-6);
+        // (x - 1)³
+        assert_roots(&solve_cubic(-3., 3., -1.), &[1.], 1e-6);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            &solve_quartic([1., -10., 35., -50., 24.]),
+            &[1., 2., 3., 4.],
+            1e-9,
+        );
+        // (x² - 1)(x² - 4), without an odd term
+        assert_roots(
+            &solve_quartic([1., 0., -5., 0., 4.]),
+            &[-2., -1., 1., 2.],
+            1e-9,
+        );
+        // 2 (x + 1)(x - 0.5)(x² + 1)
+        assert_roots(&solve_quartic([2., 1., 1., 1., -1.]), &[-1., 0.5], 1e-9);
+        // (x² + 1)(x² + 4)
+        assert_roots(&solve_quartic([1., 0., 5., 0., 4.]), &[], 1e-9);
+        // (x - 1)²(x - 3)²
+        assert_roots(&solve_quartic([1., -8., 22., -24., 9.]), &[1., 3.], 1e-4);
+        // (x + 2)²(x - 1)(x - 4)
+        assert_roots(
+            &solve_quartic([1., -1., -12., -4., 16.]),
+            &[-2., 1., 4.],
+            1e-4,
+        );
+        // (x + 3)²(x + 2)(x + 0.5), whose double root used to be lost to rounding
+        assert_roots(
+            &solve_quartic([1., 8.5, 25., 28.5, 9.]),
+            &[-3., -2., -0.5],
+            1e-4,
+        );
+    }
+}
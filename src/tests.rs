@@ -1,7 +1,16 @@
-use crate::scene::{ColorType, Light, Material, Plane, Scene, Sphere, Texture, Triangle};
-use crate::vectors::Vector;
+use crate::csg::{Csg, Operation};
+use crate::graph::Node;
+use crate::heightfield::Heightfield;
+use crate::procedural::{ColorMap, Pattern, PatternSpace, Procedural};
+use crate::scene::{
+    Annulus, Bump, ColorType, Disk, Filter, Hittable, Instance, Light, Material, Mesh, Moving,
+    MovingSphere, Pbr, Plane, Quad, Scene, Sphere, Texture, Triangle, WrapMode,
+};
+use crate::sdf::{Sdf, Shape};
+use crate::shapes::{Capsule, Cone, Cuboid, Cylinder, Torus};
+use crate::vectors::{Matrix4, Vector};
 use sdl2::pixels::Color;
-use std::sync::LazyLock; //Black magic
+use std::sync::{Arc, LazyLock}; //Black magic
 
 static ZERO_VECTOR: Vector = Vector {
     x: 0.,
@@ -20,6 +29,12 @@ pub static SCENE1: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 0)),
                     reflectivity: None,
                     transparency: Some((0.85, 0.8)),
+                    roughness: None,
+                    absorption: Some((Color::RGB(60, 220, 60), 0.8)),
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Rdeča sfera
@@ -30,6 +45,12 @@ pub static SCENE1: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: Some(0.7),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Modra sfera
@@ -40,6 +61,12 @@ pub static SCENE1: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: Some(0.2),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Plane {
@@ -49,23 +76,42 @@ pub static SCENE1: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(100, 100, 100)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![
             Light {
                 position: Vector::make(0.5, 0.0, -5.0),
                 intensity: 1.0,
+                area: None,
             },
             Light {
                 position: Vector::make(0.5, 0.0, -10.0),
                 intensity: 1.0,
+                area: None,
             },
         ],
         0.3, // ambient light
     )
 });
 
+// The camera is inside the sphere this texture is wrapped around, so it is always magnified and needs no mipmaps
+static SKY_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/sun.jpg")
+            .with_filter(Filter::Bilinear)
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+
 pub static SCENE2: LazyLock<Scene> = LazyLock::new(|| {
     Scene::make(
         vec![
@@ -73,12 +119,15 @@ pub static SCENE2: LazyLock<Scene> = LazyLock::new(|| {
                 center: Vector::make(3.0, 2.0, 0.0),
                 radius: 400.0,
                 material: Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(3.0, 2.0, 0.0), 400., point);
-                        SUN_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&SKY_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -88,6 +137,12 @@ pub static SCENE2: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Plane {
@@ -97,12 +152,21 @@ pub static SCENE2: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(100, 100, 100)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![Light {
             position: Vector::make(3.0, 15.0, 0.0),
             intensity: 1.0,
+            area: None,
         }],
         0.3,
     )
@@ -118,6 +182,12 @@ pub static SCENE3: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 0)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -127,6 +197,12 @@ pub static SCENE3: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -136,6 +212,12 @@ pub static SCENE3: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Plane {
@@ -145,17 +227,27 @@ pub static SCENE3: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(100, 100, 100)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![
             Light {
                 position: Vector::make(0.0, 0.0, -5.0),
                 intensity: 1.0,
+                area: None,
             },
             Light {
                 position: Vector::make(0.0, 0.0, 5.0),
                 intensity: 1.0,
+                area: None,
             },
         ],
         0.3,
@@ -172,6 +264,12 @@ pub static SCENE4: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 0)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -181,6 +279,12 @@ pub static SCENE4: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -190,6 +294,12 @@ pub static SCENE4: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 255)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
         ],
@@ -197,10 +307,12 @@ pub static SCENE4: LazyLock<Scene> = LazyLock::new(|| {
             Light {
                 position: Vector::make(0.0, 2.0, -4.0),
                 intensity: 0.7,
+                area: None,
             },
             Light {
                 position: Vector::make(0.0, -2.0, 4.0),
                 intensity: 0.5,
+                area: None,
             },
         ],
         0.3,
@@ -217,6 +329,12 @@ pub static SCENE5: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(144, 144, 144)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: Some(0.2),
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -226,6 +344,12 @@ pub static SCENE5: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 255)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -235,12 +359,19 @@ pub static SCENE5: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 255)),
                     reflectivity: Some(0.3),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
         ],
         vec![Light {
             position: Vector::make(-5.0, 0.0, -20.0),
             intensity: 0.75,
+            area: None,
         }],
         0.15,
     )
@@ -256,6 +387,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -265,6 +402,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -274,6 +417,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -283,6 +432,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -292,6 +447,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -301,6 +462,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -310,6 +477,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Sphere {
@@ -319,6 +492,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             }),
             Box::new(Triangle::make(
@@ -329,6 +508,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -339,6 +524,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -349,6 +540,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -359,6 +556,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -369,6 +572,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -379,6 +588,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -389,6 +604,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -399,6 +620,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -409,6 +636,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -419,6 +652,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 0, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -429,6 +668,12 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Triangle::make(
@@ -439,17 +684,331 @@ pub static SCENE6: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 255, 255)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
         ],
         vec![Light {
             position: Vector::make(1., 1., 0.),
             intensity: 0.75,
+            area: None,
         }],
         1.0,
     )
 });
 
+pub static SCENE7: LazyLock<Scene> = LazyLock::new(|| {
+    Scene::make(
+        vec![
+            // Gold
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, -2.4),
+                0.7,
+                Material {
+                    color: ColorType::Solid(Color::RGB(255, 195, 85)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: Some(Pbr {
+                        metallic: 1.0,
+                        roughness: 0.3,
+                        ior: 1.5,
+                        clearcoat: None,
+                    }),
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Chrome
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, -0.8),
+                0.7,
+                Material {
+                    color: ColorType::Solid(Color::RGB(230, 230, 235)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: Some(Pbr {
+                        metallic: 1.0,
+                        roughness: 0.0,
+                        ior: 1.5,
+                        clearcoat: None,
+                    }),
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Red plastic with clearcoat
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, 0.8),
+                0.7,
+                Material {
+                    color: ColorType::Solid(Color::RGB(200, 20, 20)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: Some(Pbr {
+                        metallic: 0.0,
+                        roughness: 0.6,
+                        ior: 1.5,
+                        clearcoat: Some((1.0, 0.05)),
+                    }),
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Rough white dielectric
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, 2.4),
+                0.7,
+                Material {
+                    color: ColorType::Solid(Color::RGB(230, 230, 230)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: Some(Pbr {
+                        metallic: 0.0,
+                        roughness: 0.9,
+                        ior: 1.5,
+                        clearcoat: None,
+                    }),
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Plane {
+                point: Vector::make(0.0, -0.7, 0.0),
+                normal: Vector::make(0.0, 1.0, 0.0),
+                material: Material {
+                    color: ColorType::Solid(Color::RGB(100, 100, 100)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: Some(Pbr {
+                        metallic: 0.0,
+                        roughness: 0.7,
+                        ior: 1.5,
+                        clearcoat: None,
+                    }),
+                    bump: None,
+                    opacity: None,
+                },
+                uv_axis: None,
+                uv_scale: 1.,
+            }),
+        ],
+        vec![
+            Light {
+                position: Vector::make(-3.0, 4.0, -3.0),
+                intensity: 0.8,
+                area: None,
+            },
+            Light {
+                position: Vector::make(-3.0, 2.0, 4.0),
+                intensity: 0.5,
+                area: None,
+            },
+        ],
+        0.2,
+    )
+});
+
+pub static SCENE8: LazyLock<Scene> = LazyLock::new(|| {
+    Scene::make(
+        vec![
+            // Glass sphere with an air bubble inside
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, -1.0),
+                1.0,
+                Material {
+                    color: ColorType::Solid(Color::RGB(220, 240, 255)),
+                    reflectivity: None,
+                    transparency: Some((0.9, 1.5)),
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Sphere::make(
+                &Vector::make(1.3, 0.2, -1.0),
+                0.4,
+                Material {
+                    color: ColorType::Solid(Color::RGB(255, 255, 255)),
+                    reflectivity: None,
+                    transparency: Some((0.95, 1.0)),
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Glass sphere half immersed in a drop of water
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.0, 1.6),
+                1.2,
+                Material {
+                    color: ColorType::Solid(Color::RGB(150, 200, 255)),
+                    reflectivity: None,
+                    transparency: Some((0.9, 1.33)),
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Sphere::make(
+                &Vector::make(1.5, 0.9, 1.6),
+                0.6,
+                Material {
+                    color: ColorType::Solid(Color::RGB(220, 255, 220)),
+                    reflectivity: None,
+                    transparency: Some((0.9, 1.5)),
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Plane {
+                point: Vector::make(0.0, -1.2, 0.0),
+                normal: Vector::make(0.0, 1.0, 0.0),
+                material: Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Checkerboard,
+                        (Color::RGB(230, 230, 230), Color::RGB(40, 40, 40)),
+                        1.,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+                uv_axis: None,
+                uv_scale: 1.,
+            }),
+        ],
+        vec![Light {
+            position: Vector::make(-3.0, 5.0, 0.0),
+            intensity: 0.8,
+            area: None,
+        }],
+        0.3,
+    )
+});
+
+/// Returns a glass octahedron (a Platonic solid) with the given center and size.
+fn glass_octahedron(center: Vector, size: f64) -> Mesh {
+    octahedron(
+        center,
+        size,
+        Material {
+            color: ColorType::Solid(Color::RGB(255, 255, 255)),
+            reflectivity: None,
+            transparency: Some((0.95, 1.6)),
+            roughness: None,
+            absorption: None,
+            dispersion: Some(8.),
+            pbr: None,
+            bump: None,
+            opacity: None,
+        },
+    )
+}
+
+/// Returns an octahedron with its corners 'size' away from 'center' along the axes.
+fn octahedron(center: Vector, size: f64, material: Material) -> Mesh {
+    let vertices = vec![
+        center + Vector::make(size, 0., 0.),
+        center + Vector::make(-size, 0., 0.),
+        center + Vector::make(0., size, 0.),
+        center + Vector::make(0., -size, 0.),
+        center + Vector::make(0., 0., size),
+        center + Vector::make(0., 0., -size),
+    ];
+    let mut faces = Vec::new();
+    for (x, sx) in [(0, 1.), (1, -1.)] {
+        for (y, sy) in [(2, 1.), (3, -1.)] {
+            for (z, sz) in [(4, 1.), (5, -1.)] {
+                // Keep the normals pointing outwards
+                faces.push(if sx * sy * sz > 0. {
+                    [x, y, z]
+                } else {
+                    [x, z, y]
+                });
+            }
+        }
+    }
+
+    Mesh::make(vertices, Vec::new(), faces, material)
+}
+
+pub static SCENE9: LazyLock<Scene> = LazyLock::new(|| {
+    let mut objects: Vec<Box<dyn Hittable>> =
+        vec![Box::new(glass_octahedron(Vector::make(1.5, 0.0, 0.0), 1.2))];
+    objects.push(Box::new(Plane {
+        point: Vector::make(0.0, -1.5, 0.0),
+        normal: Vector::make(0.0, 1.0, 0.0),
+        material: Material {
+            color: ColorType::Function(Box::new(|point: Vector| {
+                if (point.z * 2.).floor() as i64 % 2 == 0 {
+                    Color::RGB(240, 240, 240)
+                } else {
+                    Color::RGB(20, 20, 20)
+                }
+            })),
+            reflectivity: None,
+            transparency: None,
+            roughness: None,
+            absorption: None,
+            dispersion: None,
+            pbr: None,
+            bump: None,
+            opacity: None,
+        },
+        uv_axis: None,
+        uv_scale: 1.,
+    }));
+
+    Scene::make(
+        objects,
+        vec![Light {
+            position: Vector::make(-3.0, 5.0, 2.0),
+            intensity: 0.8,
+            area: None,
+        }],
+        0.3,
+    )
+});
+
 pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
     Scene::make(
         vec![
@@ -460,6 +1019,12 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(30, 30, 30)),
                     reflectivity: Some(0.05),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Plane {
@@ -469,7 +1034,15 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 150, 150)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(-3., 0., 0.),
@@ -478,7 +1051,15 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 255, 255)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 3., 0.),
@@ -487,7 +1068,15 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 255, 150)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., -3., 0.),
@@ -496,7 +1085,15 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 150, 255)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 0., 3.),
@@ -505,7 +1102,15 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 150, 255)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 0., -3.),
@@ -514,39 +1119,324 @@ pub static SCENE_H: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 150)),
                     reflectivity: Some(0.95),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![
             Light {
                 position: Vector::make(2., 0., 0.),
                 intensity: 1.,
+                area: None,
             },
             Light {
                 position: Vector::make(-2., 0., 0.),
                 intensity: 1.,
+                area: None,
             },
         ],
         0.5,
     )
 });
 
-static SUN_TEXTURE: LazyLock<Texture> = LazyLock::new(|| Texture::from_file("textures/sun.jpg"));
-static MERCURY_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/mercury.jpg"));
-static VENERA_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/venera.jpg"));
-static EARTH_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/earth.jpg"));
-static MARS_TEXTURE: LazyLock<Texture> = LazyLock::new(|| Texture::from_file("textures/mars.jpg"));
-static JUPITER_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/jupiter.jpg"));
-static SATURN_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/saturn.jpg"));
-static URANUS_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/uranus.jpg"));
-static NEPTUNE_TEXTURE: LazyLock<Texture> =
-    LazyLock::new(|| Texture::from_file("textures/neptune.jpg"));
+pub static SCENE_L: LazyLock<Scene> = LazyLock::new(|| {
+    Scene::make(
+        vec![
+            // Marble
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, -3.25),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Marble,
+                        (Color::RGB(235, 235, 230), Color::RGB(60, 60, 70)),
+                        1.,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Wood
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, -1.95),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Wood,
+                        (Color::RGB(205, 150, 90), Color::RGB(110, 60, 25)),
+                        1.,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Cells
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, -0.65),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Worley,
+                        (Color::RGB(20, 60, 120), Color::RGB(170, 220, 255)),
+                        0.3,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Beach ball, with stripes running from pole to pole
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, 0.65),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Stripes,
+                        (Color::RGB(230, 40, 40), Color::RGB(250, 250, 250)),
+                        0.125,
+                        PatternSpace::Uv,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Turbulence
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, 1.95),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Turbulence(5),
+                        (Color::RGB(250, 250, 250), Color::RGB(90, 40, 10)),
+                        0.5,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Noise
+            Box::new(Sphere::make(
+                &Vector::make(0., -0.4, 3.25),
+                0.6,
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Noise,
+                        (Color::RGB(30, 110, 30), Color::RGB(200, 230, 120)),
+                        0.25,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Plane {
+                point: Vector::make(0., -1., 0.),
+                normal: Vector::make(0., 1., 0.),
+                material: Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Checkerboard,
+                        (Color::RGB(200, 200, 200), Color::RGB(90, 90, 90)),
+                        1.,
+                        PatternSpace::Uv,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+                uv_axis: Some(Vector::make(1., 0., 0.)),
+                uv_scale: 0.5,
+            }),
+            Box::new(Plane {
+                point: Vector::make(6., 0., 0.),
+                normal: Vector::make(-1., 0., 0.),
+                material: Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Gradient,
+                        (Color::RGB(250, 200, 150), Color::RGB(60, 90, 170)),
+                        4.,
+                        PatternSpace::Solid,
+                    )),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+                uv_axis: None,
+                uv_scale: 1.,
+            }),
+            // Tinted disk in front of the wall, which half shows through it
+            Box::new(Disk::make(
+                Vector::make(5.9, 1.4, 0.),
+                Vector::make(-1., 0., 0.),
+                0.9,
+                Material {
+                    color: ColorType::Solid(Color::RGB(40, 160, 120)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: Some(0.5),
+                },
+            )),
+        ],
+        vec![Light {
+            position: Vector::make(-2., 4., -1.),
+            intensity: 0.9,
+            area: None,
+        }],
+        0.3,
+    )
+});
+
+static SUN_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/sun.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static MERCURY_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/mercury.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static MERCURY_NORMALS: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::data_from_file_or_placeholder("textures/mercury_normal.png")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static VENERA_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/venera.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static EARTH_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/earth.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static MARS_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/mars.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static MARS_HEIGHTS: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::data_from_file_or_placeholder("textures/mars.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static JUPITER_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/jupiter.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static SATURN_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/saturn.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+// One pixel row per ring radius, with see-through gaps between the rings
+static SATURN_RINGS_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/saturn_rings.png")
+            .with_wrap(WrapMode::Clamp, WrapMode::Repeat),
+    )
+});
+static URANUS_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/uranus.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+static NEPTUNE_TEXTURE: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/neptune.jpg")
+            .with_wrap(WrapMode::Repeat, WrapMode::Clamp),
+    )
+});
+
+/// Returns keyframes of a planet starting at 'start' and orbiting the sun of SCENE_J during one unit of time.
+/// Planets further away move slower, following Kepler's third law.
+fn orbit(start: Vector) -> Vec<(f64, Vector)> {
+    let sun = Vector::make(13., 0., 0.);
+    let radius = (start - sun).length();
+    let angle = 0.5 * (7. / radius).powf(1.5);
+    (0..=8)
+        .map(|step| {
+            let time = step as f64 / 8.;
+            let (sin, cos) = (angle * time).sin_cos();
+            (
+                time,
+                sun + Vector::make(radius * sin, start.y - sun.y, radius * cos),
+            )
+        })
+        .collect()
+}
 
 pub static SCENE_J: LazyLock<Scene> = LazyLock::new(|| {
     Scene::make(
@@ -556,116 +1446,182 @@ pub static SCENE_J: LazyLock<Scene> = LazyLock::new(|| {
                 &Vector::make(13., 0., 0.),
                 5.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 0.), 5., point);
-                        SUN_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&SUN_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Mercury
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 7.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 7.)),
                 1.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 7.), 1., point);
-                        MERCURY_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&MERCURY_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: Some(Bump::NormalMap(Arc::clone(&MERCURY_NORMALS))),
+                    opacity: None,
                 },
             )),
             // Venera
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 9.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 9.)),
                 1.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 9.), 1., point);
-                        VENERA_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&VENERA_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Earth
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 11.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 11.)),
                 1.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 11.), 1., point);
-                        EARTH_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&EARTH_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Mars
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 13.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 13.)),
                 1.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 13.), 1., point);
-                        MARS_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&MARS_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    // Brighter parts of the surface are treated as higher
+                    bump: Some(Bump::HeightMap(Arc::clone(&MARS_HEIGHTS), 2.)),
+                    opacity: None,
                 },
             )),
             // Jupiter
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 20.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 20.)),
                 3.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 20.), 3., point);
-                        JUPITER_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&JUPITER_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Saturn
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 29.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 29.)),
                 3.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 29.), 3., point);
-                        SATURN_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&SATURN_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
+            // Saturn's rings, tilted like its axis and moving along with it
+            Box::new(Moving::make(
+                orbit(Vector::make(13., 0., 29.)),
+                Box::new(Annulus::make(
+                    Vector::make(0., 0., 0.),
+                    Vector::make(0.4, 0.8, 0.45),
+                    3.7,
+                    6.8,
+                    Material {
+                        color: ColorType::Texture(Arc::clone(&SATURN_RINGS_TEXTURE)),
+                        reflectivity: None,
+                        transparency: None,
+                        roughness: None,
+                        absorption: None,
+                        dispersion: None,
+                        pbr: None,
+                        bump: None,
+                        opacity: None,
+                    },
+                )),
+            )),
             // Uranus
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 36.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 36.)),
                 2.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 36.), 2., point);
-                        URANUS_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&URANUS_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             // Neptune
-            Box::new(Sphere::make(
-                &Vector::make(13., 0., 42.),
+            Box::new(MovingSphere::make(
+                orbit(Vector::make(13., 0., 42.)),
                 2.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(Vector::make(13., 0., 42.), 2., point);
-                        NEPTUNE_TEXTURE.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&NEPTUNE_TEXTURE)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            // Comet
+            Box::new(MovingSphere::linear(
+                Vector::make(9., 3., 4.),
+                Vector::make(9., 2., 8.),
+                0.3,
+                Material {
+                    color: ColorType::Solid(Color::RGB(220, 240, 255)),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Plane {
@@ -675,7 +1631,15 @@ pub static SCENE_J: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(-5000., 0., 0.),
@@ -684,18 +1648,33 @@ pub static SCENE_J: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(0, 0, 0)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![Light {
             position: Vector::make(900., 45., 90.),
             intensity: 1.,
+            area: None,
         }],
-        1.,
+        0.25,
     )
 });
 
-static PEAK: LazyLock<Texture> = LazyLock::new(|| Texture::from_file("textures/peak.jpg"));
+// Mirroring hides the seam where the left and right edges of the photo meet
+static PEAK: LazyLock<Arc<Texture>> = LazyLock::new(|| {
+    Arc::new(
+        Texture::from_file_or_placeholder("textures/peak.jpg")
+            .with_wrap(WrapMode::Mirror, WrapMode::Clamp),
+    )
+});
 
 pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
     Scene::make(
@@ -704,12 +1683,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                 &ZERO_VECTOR,
                 1.,
                 Material {
-                    color: ColorType::Function(Box::new(move |point: Vector| {
-                        let (u, v) = Texture::sphere_uv(ZERO_VECTOR, 2., point);
-                        PEAK.uv_pixel_from_texture(u, v)
-                    })),
+                    color: ColorType::Texture(Arc::clone(&PEAK)),
                     reflectivity: None,
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
             )),
             Box::new(Plane {
@@ -719,7 +1701,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 150, 150)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(-3., 0., 0.),
@@ -728,7 +1718,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 255, 255)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 3., 0.),
@@ -737,7 +1735,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 255, 150)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., -3., 0.),
@@ -746,7 +1752,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 150, 255)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 0., 3.),
@@ -755,7 +1769,15 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(150, 150, 255)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
             Box::new(Plane {
                 point: Vector::make(0., 0., -3.),
@@ -764,19 +1786,691 @@ pub static PEAK_K: LazyLock<Scene> = LazyLock::new(|| {
                     color: ColorType::Solid(Color::RGB(255, 255, 150)),
                     reflectivity: Some(0.8),
                     transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
                 },
+                uv_axis: None,
+                uv_scale: 1.,
             }),
         ],
         vec![
             Light {
                 position: Vector::make(2., 0., 0.),
                 intensity: 1.,
+                area: None,
             },
             Light {
                 position: Vector::make(-2., 0., 0.),
                 intensity: 1.,
+                area: None,
             },
         ],
         0.7,
     )
 });
+
+/// Returns a cube with sides of length 1.0 centered on the origin.
+fn unit_cube(material: Material) -> Mesh {
+    let vertices = (0..8)
+        .map(|i| {
+            let coordinate = |bit: usize| if i & bit != 0 { 0.5 } else { -0.5 };
+            Vector::make(coordinate(4), coordinate(2), coordinate(1))
+        })
+        .collect();
+    let faces = vec![
+        [0, 1, 3],
+        [0, 3, 2],
+        [4, 6, 7],
+        [4, 7, 5],
+        [0, 4, 5],
+        [0, 5, 1],
+        [2, 3, 7],
+        [2, 7, 6],
+        [0, 2, 6],
+        [0, 6, 4],
+        [1, 5, 7],
+        [1, 7, 3],
+    ];
+    Mesh::make(vertices, Vec::new(), faces, material)
+}
+
+/// Returns a plain material of 'color', slightly reflective.
+fn solid(color: Color) -> Material {
+    Material {
+        color: ColorType::Solid(color),
+        reflectivity: Some(0.1),
+        transparency: None,
+        roughness: None,
+        absorption: None,
+        dispersion: None,
+        pbr: None,
+        bump: None,
+        opacity: None,
+    }
+}
+
+/// Returns the grey floor the shapes of the later scenes stand on, at 'height'.
+fn floor(height: f64) -> Plane {
+    Plane {
+        point: Vector::make(0., height, 0.),
+        normal: Vector::make(0., 1., 0.),
+        material: Material {
+            reflectivity: None,
+            ..solid(Color::RGB(180, 180, 170))
+        },
+        uv_axis: None,
+        uv_scale: 1.,
+    }
+}
+
+/// Returns the light above and to the left of the camera that lights the later scenes.
+fn overhead_light() -> Light {
+    Light {
+        position: Vector::make(-2., 4., -1.),
+        intensity: 0.9,
+        area: None,
+    }
+}
+
+pub static SCENE_I: LazyLock<Scene> = LazyLock::new(|| {
+    // Every cube and every ellipsoid is the same object, placed with a different transform
+    let cube: Arc<dyn Hittable> = Arc::new(unit_cube(Material {
+        reflectivity: Some(0.2),
+        ..solid(Color::RGB(200, 60, 50))
+    }));
+    let ball: Arc<dyn Hittable> = Arc::new(Sphere::make(
+        &ZERO_VECTOR,
+        1.,
+        Material {
+            color: ColorType::Procedural(Procedural::make(
+                Pattern::Stripes,
+                (Color::RGB(240, 240, 240), Color::RGB(40, 110, 200)),
+                0.125,
+                PatternSpace::Uv,
+            )),
+            reflectivity: None,
+            transparency: None,
+            roughness: None,
+            absorption: None,
+            dispersion: None,
+            pbr: None,
+            bump: None,
+            opacity: None,
+        },
+    ));
+
+    let up = Vector::make(0., 1., 0.);
+    let mut objects: Vec<Box<dyn Hittable>> = (0..5)
+        .map(|i| {
+            let turn = i as f64 * std::f64::consts::PI / 16.;
+            let position = Vector::make(0.5, -0.8, 0.9 * i as f64 - 1.8);
+            let transform = Matrix4::translation(position)
+                * Matrix4::rotation(up, turn)
+                * Matrix4::rotation(Vector::make(1., 0., 0.), turn)
+                * Matrix4::scaling(Vector::make(0.5, 0.5, 0.5));
+            Box::new(Instance::make(Arc::clone(&cube), transform)) as Box<dyn Hittable>
+        })
+        .collect();
+    // (center, radii along the axes, tilt around the x axis)
+    let ellipsoids = [
+        (Vector::make(3., 0.2, -2.2), Vector::make(0.5, 1.4, 0.5), 0.),
+        (
+            Vector::make(3., -0.45, 0.),
+            Vector::make(0.5, 0.5, 1.5),
+            0.4,
+        ),
+        (
+            Vector::make(3., 0.2, 2.2),
+            Vector::make(0.9, 0.9, 0.3),
+            -0.6,
+        ),
+    ];
+    for (center, radii, tilt) in ellipsoids {
+        let transform = Matrix4::translation(center)
+            * Matrix4::rotation(Vector::make(1., 0., 0.), tilt)
+            * Matrix4::scaling(radii);
+        objects.push(Box::new(Instance::make(Arc::clone(&ball), transform)));
+    }
+    objects.push(Box::new(floor(-1.2)));
+
+    Scene::make(objects, vec![overhead_light()], 0.3)
+});
+
+/// Orbits of the orrery and how far each turns in radians per step, the moons faster than the planets.
+const ORRERY_ORBITS: [(&str, f64); 5] = [
+    ("earth orbit", 0.3),
+    ("moon orbit", 1.2),
+    ("mars orbit", 0.16),
+    ("phobos orbit", 1.5),
+    ("deimos orbit", 0.8),
+];
+
+/// Returns a group that turns around the y axis of its parent, holding 'body' at 'distance' from the center.
+fn orbit_node(name: &str, distance: f64, angle: f64, body: Node) -> Node {
+    let offset = Matrix4::translation(Vector::make(distance, 0., 0.));
+    Node::group(name, vec![body.with_transform(offset)])
+        .with_transform(Matrix4::rotation(Vector::make(0., 1., 0.), angle))
+}
+
+/// Returns a textured sphere around the origin of its node.
+fn planet_node(name: &str, radius: f64, texture: &Arc<Texture>) -> Node {
+    Node::object(
+        name,
+        Box::new(Sphere::make(
+            &ZERO_VECTOR,
+            radius,
+            Material {
+                color: ColorType::Texture(Arc::clone(texture)),
+                reflectivity: None,
+                transparency: None,
+                roughness: None,
+                absorption: None,
+                dispersion: None,
+                pbr: None,
+                bump: None,
+                opacity: None,
+            },
+        )),
+    )
+}
+
+/// Returns a scene graph of the sun with planets and their moons, which 'advance_orrery' moves along their orbits.
+pub fn orrery() -> Scene {
+    // All moons are the same sphere, scaled to size
+    let moon: Arc<dyn Hittable> = Arc::new(Sphere::make(
+        &ZERO_VECTOR,
+        1.,
+        Material {
+            color: ColorType::Texture(Arc::clone(&MERCURY_TEXTURE)),
+            reflectivity: None,
+            transparency: None,
+            roughness: None,
+            absorption: None,
+            dispersion: None,
+            pbr: None,
+            bump: None,
+            opacity: None,
+        },
+    ));
+    let moon_node = |name: &str, radius: f64| {
+        let size = Vector::make(radius, radius, radius);
+        Node::object(
+            name,
+            Box::new(Instance::make(Arc::clone(&moon), Matrix4::scaling(size))),
+        )
+    };
+
+    let earth = Node::group(
+        "earth",
+        vec![
+            planet_node("earth body", 0.5, &EARTH_TEXTURE),
+            orbit_node("moon orbit", 0.9, 0., moon_node("moon", 0.14)),
+        ],
+    );
+    let mars = Node::group(
+        "mars",
+        vec![
+            planet_node("mars body", 0.4, &MARS_TEXTURE),
+            // The moons of Mars are darker than ours
+            Node::group(
+                "mars moons",
+                vec![
+                    orbit_node("phobos orbit", 0.6, 1., moon_node("phobos", 0.08)),
+                    orbit_node("deimos orbit", 0.9, 3., moon_node("deimos", 0.07)),
+                ],
+            )
+            .with_material(Material {
+                color: ColorType::Solid(Color::RGB(110, 90, 80)),
+                reflectivity: None,
+                transparency: None,
+                roughness: None,
+                absorption: None,
+                dispersion: None,
+                pbr: None,
+                bump: None,
+                opacity: None,
+            }),
+        ],
+    );
+    let system = Node::group(
+        "solar system",
+        vec![
+            planet_node("sun", 1., &SUN_TEXTURE),
+            orbit_node("earth orbit", 2.5, -1.2, earth),
+            orbit_node("mars orbit", 3.8, 1.3, mars),
+        ],
+    )
+    .with_transform(
+        Matrix4::translation(Vector::make(1.5, -0.6, 0.))
+            * Matrix4::rotation(Vector::make(0., 0., 1.), 0.5)
+            * Matrix4::scaling(Vector::make(0.7, 0.7, 0.7)),
+    );
+
+    Scene::make(
+        Vec::new(),
+        vec![Light {
+            position: Vector::make(-10., 20., -10.),
+            intensity: 0.9,
+            area: None,
+        }],
+        0.25,
+    )
+    .with_nodes(vec![system])
+}
+
+pub static SCENE_O: LazyLock<Scene> = LazyLock::new(orrery);
+
+/// Moves the planets and moons of an 'orrery' scene 'steps' steps along their orbits.
+pub fn advance_orrery(scene: &mut Scene, steps: f64) {
+    let up = Vector::make(0., 1., 0.);
+    for (name, speed) in ORRERY_ORBITS {
+        if let Some(node) = scene.node_mut(name) {
+            node.set_transform(node.transform() * Matrix4::rotation(up, speed * steps));
+        }
+    }
+}
+
+pub static SCENE_G: LazyLock<Scene> = LazyLock::new(|| {
+    let cube = |center: Vector, size: f64, turn: f64, color: Color| {
+        let transform = Matrix4::translation(center)
+            * Matrix4::rotation(Vector::make(1., 1., 0.), turn)
+            * Matrix4::scaling(Vector::make(size, size, size));
+        Box::new(Instance::make(Arc::new(unit_cube(solid(color))), transform))
+    };
+
+    Scene::make(
+        vec![
+            // A sphere with a cube carved out of it, whose faces show inside the bite
+            Box::new(Csg::make(
+                Operation::Difference,
+                Box::new(Sphere::make(
+                    &Vector::make(0.5, 0., -2.),
+                    1.,
+                    solid(Color::RGB(70, 130, 200)),
+                )),
+                cube(
+                    Vector::make(-0.2, 0.7, -1.4),
+                    1.3,
+                    0.3,
+                    Color::RGB(240, 200, 80),
+                ),
+            )),
+            // Cutting off the corners of a cube with an octahedron
+            Box::new(Csg::make(
+                Operation::Intersection,
+                cube(Vector::make(0.5, 0., 0.), 1.3, 0.4, Color::RGB(200, 70, 60)),
+                Box::new(Instance::make(
+                    Arc::new(octahedron(ZERO_VECTOR, 1., solid(Color::RGB(90, 180, 90)))),
+                    Matrix4::translation(Vector::make(0.5, 0., 0.))
+                        * Matrix4::rotation(Vector::make(1., 1., 0.), 0.4),
+                )),
+            )),
+            // Two spheres joined together with a hole through both of them
+            Box::new(Csg::make(
+                Operation::Difference,
+                Box::new(Csg::make(
+                    Operation::Union,
+                    Box::new(Sphere::make(
+                        &Vector::make(0.5, 0.3, 1.7),
+                        0.7,
+                        solid(Color::RGB(220, 220, 220)),
+                    )),
+                    Box::new(Sphere::make(
+                        &Vector::make(0.5, -0.3, 2.4),
+                        0.7,
+                        solid(Color::RGB(220, 220, 220)),
+                    )),
+                )),
+                // A long, thin ellipsoid through where the spheres meet, pointing towards the camera
+                Box::new(Instance::make(
+                    Arc::new(Sphere::make(
+                        &ZERO_VECTOR,
+                        1.,
+                        solid(Color::RGB(160, 60, 160)),
+                    )),
+                    Matrix4::translation(Vector::make(0.5, 0., 2.05))
+                        * Matrix4::rotation(Vector::make(0., 1., 0.), -0.55)
+                        * Matrix4::scaling(Vector::make(1.6, 0.3, 0.3)),
+                )),
+            )),
+            Box::new(floor(-1.2)),
+        ],
+        vec![overhead_light()],
+        0.3,
+    )
+});
+
+/// Analytic shapes, with UV checkerboards showing how textures wrap around them
+pub static SCENE_U: LazyLock<Scene> = LazyLock::new(|| {
+    let checkered = |color: Color, size: f64| Material {
+        color: ColorType::Procedural(Procedural::make(
+            Pattern::Checkerboard,
+            (color, Color::RGB(240, 240, 230)),
+            size,
+            PatternSpace::Uv,
+        )),
+        ..solid(color)
+    };
+    let up = Vector::make(0., 1., 0.);
+
+    Scene::make(
+        vec![
+            Box::new(Cuboid::make(
+                Vector::make(0.8, -1., -2.9),
+                Vector::make(1.6, -0.2, -2.1),
+                checkered(Color::RGB(200, 70, 60), 0.25),
+            )),
+            Box::new(Cylinder::make(
+                Vector::make(1.2, -1., -1.2),
+                Vector::make(1.2, 0.2, -1.2),
+                0.45,
+                checkered(Color::RGB(70, 130, 200), 0.125),
+            )),
+            Box::new(Cone::make(
+                Vector::make(1.2, -1., 0.),
+                Vector::make(1.2, 0.4, 0.),
+                0.55,
+                checkered(Color::RGB(90, 180, 90), 0.125),
+            )),
+            Box::new(Torus::make(
+                Vector::make(1.2, -0.35, 1.3),
+                Vector::make(-0.6, 1., 0.2),
+                0.45,
+                0.18,
+                checkered(Color::RGB(240, 200, 80), 0.1),
+            )),
+            Box::new(Capsule::make(
+                Vector::make(1.4, -0.75, 2.3),
+                Vector::make(0.9, 0.1, 2.8),
+                0.25,
+                checkered(Color::RGB(160, 60, 160), 0.125),
+            )),
+            // Turned boxes standing on one corner
+            Box::new(Cuboid::oriented(
+                Vector::make(3.8, -0.2, 1.9),
+                Vector::make(1., 1., 1.),
+                Vector::make(1., 0., 1.),
+                0.95,
+                Material {
+                    reflectivity: Some(0.8),
+                    ..solid(Color::RGB(200, 200, 210))
+                },
+            )),
+            // A nut, a square block with its corners rounded off by a cylinder and a hole drilled through it
+            Box::new(Csg::make(
+                Operation::Difference,
+                Box::new(Csg::make(
+                    Operation::Intersection,
+                    Box::new(Cuboid::oriented(
+                        Vector::make(3.8, -0.6, -0.6),
+                        Vector::make(1.6, 0.7, 1.6),
+                        up,
+                        0.3,
+                        solid(Color::RGB(180, 150, 90)),
+                    )),
+                    Box::new(Cylinder::make(
+                        Vector::make(3.8, -1., -0.6),
+                        Vector::make(3.8, -0.2, -0.6),
+                        0.95,
+                        solid(Color::RGB(180, 150, 90)),
+                    )),
+                )),
+                Box::new(Cylinder::make(
+                    Vector::make(3.8, -1.5, -0.6),
+                    Vector::make(3.8, 0.3, -0.6),
+                    0.45,
+                    solid(Color::RGB(120, 100, 60)),
+                )),
+            )),
+            Box::new(floor(-1.)),
+        ],
+        vec![overhead_light()],
+        0.3,
+    )
+});
+
+/// A room built from quads, with a mirror, a framed picture, a doorway and a soft light in the ceiling
+pub static SCENE_R: LazyLock<Scene> = LazyLock::new(|| {
+    let matte = |color: Color| Material {
+        reflectivity: None,
+        ..solid(color)
+    };
+    let wall = || matte(Color::RGB(225, 215, 195));
+    let (floor, ceiling) = (-1.2, 2.);
+    let ceiling_light = Quad::make(
+        Vector::make(-1.4, ceiling - 0.05, -0.8),
+        Vector::make(1.6, 0., 0.),
+        Vector::make(0., 0., 1.6),
+        matte(Color::RGB(255, 255, 255)),
+    );
+
+    Scene::make(
+        vec![
+            // Floor and ceiling
+            Box::new(Quad::make(
+                Vector::make(-4., floor, -3.),
+                Vector::make(8., 0., 0.),
+                Vector::make(0., 0., 6.),
+                Material {
+                    color: ColorType::Procedural(Procedural::make(
+                        Pattern::Checkerboard,
+                        (Color::RGB(90, 60, 40), Color::RGB(200, 170, 130)),
+                        0.125,
+                        PatternSpace::Uv,
+                    )),
+                    reflectivity: Some(0.1),
+                    ..matte(Color::RGB(0, 0, 0))
+                },
+            )),
+            Box::new(Quad::make(
+                Vector::make(-4., ceiling, -3.),
+                Vector::make(8., 0., 0.),
+                Vector::make(0., 0., 6.),
+                wall(),
+            )),
+            // Side walls, with a mirror on the left one and a picture on the right one
+            Box::new(Quad::make(
+                Vector::make(-4., floor, -3.),
+                Vector::make(8., 0., 0.),
+                Vector::make(0., ceiling - floor, 0.),
+                wall(),
+            )),
+            Box::new(Quad::make(
+                Vector::make(0., -0.7, -2.99),
+                Vector::make(2.6, 0., 0.),
+                Vector::make(0., 2., 0.),
+                Material {
+                    reflectivity: Some(0.95),
+                    ..matte(Color::RGB(240, 240, 240))
+                },
+            )),
+            Box::new(Quad::make(
+                Vector::make(-4., floor, 3.),
+                Vector::make(8., 0., 0.),
+                Vector::make(0., ceiling - floor, 0.),
+                wall(),
+            )),
+            Box::new(Quad::make(
+                Vector::make(0.5, -0.3, 2.99),
+                Vector::make(2., 0., 0.),
+                Vector::make(0., 1.5, 0.),
+                matte(Color::RGB(70, 45, 25)),
+            )),
+            Box::new(Quad::make(
+                Vector::make(0.6, -0.2, 2.98),
+                Vector::make(1.8, 0., 0.),
+                Vector::make(0., 1.3, 0.),
+                Material {
+                    color: ColorType::Texture(Arc::clone(&PEAK)),
+                    ..wall()
+                },
+            )),
+            // The far wall, around an open doorway
+            Box::new(Quad::make(
+                Vector::make(4., floor, -3.),
+                Vector::make(0., 0., 2.4),
+                Vector::make(0., ceiling - floor, 0.),
+                wall(),
+            )),
+            Box::new(Quad::make(
+                Vector::make(4., floor, 0.6),
+                Vector::make(0., 0., 2.4),
+                Vector::make(0., ceiling - floor, 0.),
+                wall(),
+            )),
+            Box::new(Quad::make(
+                Vector::make(4., 1., -0.6),
+                Vector::make(0., 0., 1.2),
+                Vector::make(0., ceiling - 1., 0.),
+                wall(),
+            )),
+            Box::new(Quad::make(
+                Vector::make(-4., floor, -3.),
+                Vector::make(0., 0., 6.),
+                Vector::make(0., ceiling - floor, 0.),
+                wall(),
+            )),
+            Box::new(Sphere::make(
+                &Vector::make(2.4, floor + 0.6, -1.),
+                0.6,
+                Material {
+                    reflectivity: Some(0.2),
+                    ..matte(Color::RGB(70, 130, 200))
+                },
+            )),
+            Box::new(Cuboid::oriented(
+                Vector::make(2.6, floor + 0.4, 1.),
+                Vector::make(0.8, 0.8, 0.8),
+                Vector::make(0., 1., 0.),
+                0.5,
+                matte(Color::RGB(200, 70, 60)),
+            )),
+        ],
+        vec![ceiling_light.light(0.9)],
+        0.35,
+    )
+});
+
+/// Shapes sphere traced from distance functions, standing among ordinary objects
+pub static SCENE_T: LazyLock<Scene> = LazyLock::new(|| {
+    // Blobs melting into each other
+    let blobs = Shape::Sphere(0.5)
+        .smooth_union(
+            Shape::Sphere(0.35).translated(Vector::make(0., 0.55, 0.35)),
+            0.3,
+        )
+        .smooth_union(
+            Shape::Sphere(0.3).translated(Vector::make(0., -0.1, -0.55)),
+            0.3,
+        )
+        .smooth_union(
+            Shape::Torus(0.55, 0.1).translated(Vector::make(0., -0.45, 0.)),
+            0.2,
+        );
+    // A rounded box with a ball taken out of each corner on top, twisted around its height
+    let tower = Shape::Cuboid(Vector::make(0.35, 0.8, 0.35))
+        .smooth_union(Shape::Capsule(0.8, 0.2), 0.1)
+        .subtract(Shape::Sphere(0.3).translated(Vector::make(0.35, 0.8, 0.35)))
+        .subtract(Shape::Sphere(0.3).translated(Vector::make(-0.35, 0.8, -0.35)))
+        .twist(1.2);
+
+    Scene::make(
+        vec![
+            Box::new(Sdf::make(
+                Shape::Mandelbulb(8., 10).scaled(1.5),
+                Vector::make(2.6, 0.3, 0.),
+                1.8,
+                Material {
+                    color: ColorType::Solid(Color::RGB(230, 180, 90)),
+                    reflectivity: Some(0.2),
+                    ..solid(Color::RGB(0, 0, 0))
+                },
+            )),
+            Box::new(Sdf::make(
+                blobs,
+                Vector::make(0.8, -0.5, -1.9),
+                1.,
+                solid(Color::RGB(90, 180, 90)),
+            )),
+            Box::new(Sdf::make(
+                tower,
+                Vector::make(0.8, -0.3, 1.9),
+                1.2,
+                solid(Color::RGB(200, 70, 60)),
+            )),
+            // An ordinary glass sphere, showing the shapes through it
+            Box::new(Sphere::make(
+                &Vector::make(-0.2, -0.75, 0.3),
+                0.35,
+                Material {
+                    transparency: Some((0.9, 1.5)),
+                    ..solid(Color::RGB(255, 255, 255))
+                },
+            )),
+            Box::new(floor(-1.1)),
+        ],
+        vec![overhead_light()],
+        0.3,
+    )
+});
+
+/// An island raised from a grayscale heightmap, colored by height and slope, with the sea around it
+pub static SCENE_Y: LazyLock<Scene> = LazyLock::new(|| {
+    let colors = ColorMap::make(vec![
+        (-1.5, Color::RGB(200, 185, 130)),
+        (-1.3, Color::RGB(80, 140, 60)),
+        (-0.8, Color::RGB(60, 100, 45)),
+        (-0.3, Color::RGB(120, 105, 90)),
+        (0.1, Color::RGB(245, 245, 250)),
+    ])
+    .with_steep(0.9, Color::RGB(110, 100, 95));
+
+    Scene::make(
+        vec![
+            Box::new(Heightfield::from_file_or_flat(
+                "textures/terrain.png",
+                256,
+                Vector::make(-1., -1.6, -4.5),
+                Vector::make(9., 2.4, 9.),
+                Material {
+                    color: ColorType::Terrain(colors),
+                    reflectivity: None,
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+            )),
+            Box::new(Plane {
+                point: Vector::make(0., -1.45, 0.),
+                normal: Vector::make(0., 1., 0.),
+                material: Material {
+                    color: ColorType::Solid(Color::RGB(40, 80, 120)),
+                    reflectivity: Some(0.5),
+                    transparency: None,
+                    roughness: None,
+                    absorption: None,
+                    dispersion: None,
+                    pbr: None,
+                    bump: None,
+                    opacity: None,
+                },
+                uv_axis: None,
+                uv_scale: 1.,
+            }),
+        ],
+        vec![Light {
+            position: Vector::make(-4., 6., -6.),
+            intensity: 1.,
+            area: None,
+        }],
+        0.3,
+    )
+});
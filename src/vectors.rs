@@ -1,4 +1,4 @@
-use std::{ops::{Add, Div, Mul, Neg, Sub}};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Vector {
@@ -15,7 +15,7 @@ impl Vector {
     pub fn length(&self) -> f64 {
         return f64::sqrt(self.x.powi(2) + self.y.powi(2) + self.z.powi(2));
     }
-    
+
     /// Returns a normalized vector of 'self' (length 1)
     pub fn normalized(self: &Vector) -> Vector {
         let length = self.length();
@@ -42,6 +42,16 @@ impl Vector {
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    /// Returns the component-wise product of the vectors 'self' and 'other'.
+    pub fn multiply(&self, other: &Vector) -> Vector {
+        Vector {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+
     /// Returns the angle in radians between the vectors 'self' and 'other'.
     pub fn angle_with(&self, other: &Vector) -> f64 {
         f64::asin(self.normalized().dot(&other.normalized()))
@@ -52,6 +62,19 @@ impl Vector {
         *self - *normal * 2.0 * self.dot(normal)
     }
 
+    /// Returns two unit vectors that together with 'self' form an orthonormal basis.
+    pub fn orthonormal_basis(&self) -> (Vector, Vector) {
+        let w = self.normalized();
+        let helper = if w.x.abs() > 0.9 {
+            Vector::make(0.0, 1.0, 0.0)
+        } else {
+            Vector::make(1.0, 0.0, 0.0)
+        };
+        let u = helper.cross(&w).normalized();
+        let v = w.cross(&u);
+        (u, v)
+    }
+
     /// Returns the direction vector of the ray 'self' after it is refracted through a surface with the given 'normal' and 'refraction_index'.
     pub fn refract(&self, normal: &Vector, refraction_index: f64) -> Vector {
         assert!(refraction_index > 0.);
@@ -72,6 +95,140 @@ impl Vector {
     }
 }
 
+/// A 4x4 matrix of an affine transform, applied to vectors as columns from the left.
+#[derive(Debug, Copy, Clone)]
+pub struct Matrix4 {
+    pub rows: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn identity() -> Self {
+        Matrix4 {
+            rows: [
+                [1., 0., 0., 0.],
+                [0., 1., 0., 0.],
+                [0., 0., 1., 0.],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    /// Returns the transform that moves points by 'offset'.
+    pub fn translation(offset: Vector) -> Self {
+        let mut matrix = Matrix4::identity();
+        matrix.rows[0][3] = offset.x;
+        matrix.rows[1][3] = offset.y;
+        matrix.rows[2][3] = offset.z;
+        matrix
+    }
+
+    /// Returns the transform that stretches space along the axes by the components of 'factors'.
+    pub fn scaling(factors: Vector) -> Self {
+        let mut matrix = Matrix4::identity();
+        matrix.rows[0][0] = factors.x;
+        matrix.rows[1][1] = factors.y;
+        matrix.rows[2][2] = factors.z;
+        matrix
+    }
+
+    /// Returns the transform that rotates space by 'angle' radians around 'axis', counter-clockwise when the axis points at the viewer.
+    pub fn rotation(axis: Vector, angle: f64) -> Self {
+        let Vector { x, y, z } = axis.normalized();
+        let (sin, cos) = angle.sin_cos();
+        let t = 1. - cos;
+        Matrix4 {
+            rows: [
+                [
+                    t * x * x + cos,
+                    t * x * y - sin * z,
+                    t * x * z + sin * y,
+                    0.,
+                ],
+                [
+                    t * x * y + sin * z,
+                    t * y * y + cos,
+                    t * y * z - sin * x,
+                    0.,
+                ],
+                [
+                    t * x * z - sin * y,
+                    t * y * z + sin * x,
+                    t * z * z + cos,
+                    0.,
+                ],
+                [0., 0., 0., 1.],
+            ],
+        }
+    }
+
+    /// Returns the point 'point' moved by the transform.
+    pub fn transform_point(&self, point: Vector) -> Vector {
+        self.transform_vector(point)
+            + Vector::make(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    /// Returns the direction 'vector' turned and stretched by the transform, which doesn't move directions.
+    pub fn transform_vector(&self, vector: Vector) -> Vector {
+        let row = |i: usize| {
+            self.rows[i][0] * vector.x + self.rows[i][1] * vector.y + self.rows[i][2] * vector.z
+        };
+        Vector::make(row(0), row(1), row(2))
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Matrix4 { rows }
+    }
+
+    /// Returns the transform that undoes 'self', or None if it flattens space so it can't be undone.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        // The linear part is inverted with its adjugate, then the translation is undone in the inverted space
+        let m = |i: usize, j: usize| self.rows[i][j];
+        let cofactor = |i: usize, j: usize| {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+            m(i1, j1) * m(i2, j2) - m(i1, j2) * m(i2, j1)
+        };
+        let determinant =
+            m(0, 0) * cofactor(0, 0) + m(0, 1) * cofactor(0, 1) + m(0, 2) * cofactor(0, 2);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+
+        let mut inverse = Matrix4::identity();
+        for i in 0..3 {
+            for j in 0..3 {
+                inverse.rows[i][j] = cofactor(j, i) / determinant;
+            }
+        }
+        let translation = inverse.transform_vector(Vector::make(m(0, 3), m(1, 3), m(2, 3)));
+        for i in 0..3 {
+            inverse.rows[i][3] = -[translation.x, translation.y, translation.z][i];
+        }
+        Some(inverse)
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    /// Returns the transform that applies 'other' first and then 'self'.
+    fn mul(self, other: Matrix4) -> Matrix4 {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * other.rows[k][j]).sum();
+            }
+        }
+        Matrix4 { rows }
+    }
+}
+
 impl Add for Vector {
     type Output = Self;
 